@@ -0,0 +1,37 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    // 日志内容不是合法的十六进制
+    InvalidHex,
+    // 解码后不是 UTF-8 字符串
+    InvalidUtf8,
+    // 字段数量不符合格式
+    FieldCount { expected: usize, found: usize },
+    // 未知的事件类型编号
+    UnknownEventType(String),
+    // 数字字段解析失败
+    InvalidNumber(String),
+    // 红包ID 不是 20 字节
+    InvalidGiveawayId(String),
+    // 地址不是合法的 base58 公钥
+    InvalidPubkey(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex => write!(f, "log payload is not valid hex"),
+            DecodeError::InvalidUtf8 => write!(f, "log payload is not valid utf-8"),
+            DecodeError::FieldCount { expected, found } => {
+                write!(f, "expected {} fields, found {}", expected, found)
+            }
+            DecodeError::UnknownEventType(value) => write!(f, "unknown event type: {}", value),
+            DecodeError::InvalidNumber(value) => write!(f, "invalid number: {}", value),
+            DecodeError::InvalidGiveawayId(value) => write!(f, "invalid giveaway id: {}", value),
+            DecodeError::InvalidPubkey(value) => write!(f, "invalid pubkey: {}", value),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
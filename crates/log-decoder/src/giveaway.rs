@@ -0,0 +1,78 @@
+use crate::errors::DecodeError;
+use crate::{decode_payload, parse_amount, parse_pubkey};
+
+// 与 giveaway 程序 event_type.rs 保持一致
+// 注意：改为事件之前的 refund_token 以 Receive (2) 记录退款，之后才使用 Refund (3)。
+// 旧日志格式中两者完全相同，解析链上历史数据时，代币红包的 Receive 记录可能是退款，
+// 需要结合交易指令（RefundToken）或钱包是否为创建者区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    Create = 1,
+    Receive = 2,
+    Refund = 3,
+    Cancel = 4,
+    TopUp = 5,
+    Pause = 6,
+    Resume = 7,
+}
+
+impl TryFrom<u32> for EventType {
+    type Error = DecodeError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(EventType::Create),
+            2 => Ok(EventType::Receive),
+            3 => Ok(EventType::Refund),
+            4 => Ok(EventType::Cancel),
+            5 => Ok(EventType::TopUp),
+            6 => Ok(EventType::Pause),
+            7 => Ok(EventType::Resume),
+            _ => Err(DecodeError::UnknownEventType(value.to_string())),
+        }
+    }
+}
+
+// 日志格式：事件类型,红包ID,金额,钱包地址,币种（主币为 0）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GiveawayRecord {
+    pub event_type: EventType,
+    pub giveaway_id: [u8; 20],
+    pub amount: u128,
+    pub wallet: [u8; 32],
+    pub token_mint: Option<[u8; 32]>, // None 表示主币红包
+}
+
+pub fn decode(payload: &str) -> Result<GiveawayRecord, DecodeError> {
+    let content = decode_payload(payload)?;
+    let fields: Vec<&str> = content.split(',').collect();
+    if fields.len() != 5 {
+        return Err(DecodeError::FieldCount {
+            expected: 5,
+            found: fields.len(),
+        });
+    }
+
+    let event_type = fields[0]
+        .parse::<u32>()
+        .map_err(|_| DecodeError::UnknownEventType(fields[0].to_string()))
+        .and_then(EventType::try_from)?;
+
+    let giveaway_id = hex::decode(fields[1])
+        .ok()
+        .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+        .ok_or_else(|| DecodeError::InvalidGiveawayId(fields[1].to_string()))?;
+
+    let token_mint = match fields[4] {
+        "0" => None,
+        mint => Some(parse_pubkey(mint)?),
+    };
+
+    Ok(GiveawayRecord {
+        event_type,
+        giveaway_id,
+        amount: parse_amount(fields[2])?,
+        wallet: parse_pubkey(fields[3])?,
+        token_mint,
+    })
+}
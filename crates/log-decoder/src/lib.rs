@@ -0,0 +1,64 @@
+mod errors;
+mod giveaway;
+mod reelpay;
+
+pub use errors::DecodeError;
+pub use giveaway::{EventType, GiveawayRecord};
+pub use reelpay::ReelPayRecord;
+
+const LOG_PREFIX: &str = "Program log: ";
+const GIVEAWAY_PREFIX: &str = "Giveaway ";
+const REELPAY_PREFIX: &str = "ReelPay ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    Giveaway(GiveawayRecord),
+    ReelPay(ReelPayRecord),
+}
+
+// 解析单行日志，非 Giveaway/ReelPay 日志返回 None
+pub fn decode_line(line: &str) -> Result<Option<Record>, DecodeError> {
+    let line = line.strip_prefix(LOG_PREFIX).unwrap_or(line);
+
+    if let Some(payload) = line.strip_prefix(GIVEAWAY_PREFIX) {
+        return giveaway::decode(payload).map(|record| Some(Record::Giveaway(record)));
+    }
+    if let Some(payload) = line.strip_prefix(REELPAY_PREFIX) {
+        return reelpay::decode(payload).map(|record| Some(Record::ReelPay(record)));
+    }
+    Ok(None)
+}
+
+// 解析交易的全部日志，按出现顺序返回
+pub fn decode_logs<I, S>(logs: I) -> Result<Vec<Record>, DecodeError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut records = Vec::new();
+    for line in logs {
+        if let Some(record) = decode_line(line.as_ref())? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+fn decode_payload(payload: &str) -> Result<String, DecodeError> {
+    let bytes = hex::decode(payload.trim()).map_err(|_| DecodeError::InvalidHex)?;
+    String::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)
+}
+
+fn parse_amount(value: &str) -> Result<u128, DecodeError> {
+    value
+        .parse::<u128>()
+        .map_err(|_| DecodeError::InvalidNumber(value.to_string()))
+}
+
+fn parse_pubkey(value: &str) -> Result<[u8; 32], DecodeError> {
+    bs58::decode(value)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| DecodeError::InvalidPubkey(value.to_string()))
+}
@@ -0,0 +1,51 @@
+use crate::errors::DecodeError;
+use crate::{decode_payload, parse_amount, parse_pubkey};
+
+// pay_native 日志格式：订单号,金额
+// pay_token 日志格式：订单号,金额,币种
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReelPayRecord {
+    PayNative {
+        order_id: String,
+        amount: u128,
+    },
+    PayToken {
+        order_id: String,
+        amount: u128,
+        token_mint: [u8; 32],
+    },
+}
+
+pub fn decode(payload: &str) -> Result<ReelPayRecord, DecodeError> {
+    let content = decode_payload(payload)?;
+
+    // 订单号可能包含逗号，从右往左解析
+    let mut fields = content.rsplitn(3, ',');
+    let last = fields.next().unwrap_or_default();
+    let middle = fields.next();
+    let rest = fields.next();
+
+    match (middle, rest) {
+        (None, _) => Err(DecodeError::FieldCount {
+            expected: 2,
+            found: 1,
+        }),
+        (Some(amount), Some(order_id)) if parse_pubkey(last).is_ok() => {
+            Ok(ReelPayRecord::PayToken {
+                order_id: order_id.to_string(),
+                amount: parse_amount(amount)?,
+                token_mint: parse_pubkey(last)?,
+            })
+        }
+        (Some(middle), rest) => {
+            let order_id = match rest {
+                Some(rest) => format!("{},{}", rest, middle),
+                None => middle.to_string(),
+            };
+            Ok(ReelPayRecord::PayNative {
+                order_id,
+                amount: parse_amount(last)?,
+            })
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use log_decoder::{
+    decode_line, decode_logs, DecodeError, EventType, GiveawayRecord, Record, ReelPayRecord,
+};
+
+const PAYER: &str = "FvLMWKzRA2if2aJpFd4jFG9qQZhmzWjLAf5sPCQ1Xvsh";
+const USDT: &str = "USDJASjwdezW9T1oCqDG2ui1PWrTt28QE1s7KBmaids";
+const GIVEAWAY_ID: &str = "5b38da6a701c568545dcfcb03fcb875f56beddc4";
+
+// giveaway create_put_giveaway: "1,<id>,10000000000,<payer>,0"
+const CREATE_PUT: &str = "Program log: Giveaway 312c356233386461366137303163353638353435646366636230336663623837356635366265646463342c31303030303030303030302c46764c4d574b7a524132696632614a704664346a46473971515a686d7a576a4c4166357350435131587673682c30";
+// giveaway receive_non_put_giveaway: "2,<id>,300000,<payer>,<usdt>"
+const RECEIVE_TOKEN: &str = "Program log: Giveaway 322c356233386461366137303163353638353435646366636230336663623837356635366265646463342c3330303030302c46764c4d574b7a524132696632614a704664346a46473971515a686d7a576a4c4166357350435131587673682c5553444a41536a7764657a573954316f43714447327569315057725474323851453173374b426d61696473";
+// giveaway refund_token: "3,<id>,9700000,<payer>,<usdt>"
+const REFUND_TOKEN: &str = "Program log: Giveaway 332c356233386461366137303163353638353435646366636230336663623837356635366265646463342c393730303030302c46764c4d574b7a524132696632614a704664346a46473971515a686d7a576a4c4166357350435131587673682c5553444a41536a7764657a573954316f43714447327569315057725474323851453173374b426d61696473";
+// 迁移前的 refund_token 使用领取类型: "2,<id>,9700000,<payer>,<usdt>"
+const LEGACY_REFUND_TOKEN: &str = "Program log: Giveaway 322c356233386461366137303163353638353435646366636230336663623837356635366265646463342c393730303030302c46764c4d574b7a524132696632614a704664346a46473971515a686d7a576a4c4166357350435131587673682c5553444a41536a7764657a573954316f43714447327569315057725474323851453173374b426d61696473";
+// reelpay pay_native: "ORD20240318001,1000000000"
+const PAY_NATIVE: &str = "Program log: ReelPay 4f524432303234303331383030312c31303030303030303030";
+// reelpay pay_token: "ORD20240318002,2500000,<usdt>"
+const PAY_TOKEN: &str = "Program log: ReelPay 4f524432303234303331383030322c323530303030302c5553444a41536a7764657a573954316f43714447327569315057725474323851453173374b426d61696473";
+// reelpay pay_native，订单号中带逗号: "ORD,2024,7,1500"
+const PAY_NATIVE_COMMA: &str = "Program log: ReelPay 4f52442c323032342c372c31353030";
+
+fn pubkey(value: &str) -> [u8; 32] {
+    bs58::decode(value).into_vec().unwrap().try_into().unwrap()
+}
+
+fn giveaway_id() -> [u8; 20] {
+    hex::decode(GIVEAWAY_ID).unwrap().try_into().unwrap()
+}
+
+#[test]
+fn decode_create_put_giveaway() {
+    assert_eq!(
+        decode_line(CREATE_PUT).unwrap(),
+        Some(Record::Giveaway(GiveawayRecord {
+            event_type: EventType::Create,
+            giveaway_id: giveaway_id(),
+            amount: 10000000000,
+            wallet: pubkey(PAYER),
+            token_mint: None,
+        }))
+    );
+}
+
+#[test]
+fn decode_receive_and_refund_token_giveaway() {
+    assert_eq!(
+        decode_line(RECEIVE_TOKEN).unwrap(),
+        Some(Record::Giveaway(GiveawayRecord {
+            event_type: EventType::Receive,
+            giveaway_id: giveaway_id(),
+            amount: 300000,
+            wallet: pubkey(PAYER),
+            token_mint: Some(pubkey(USDT)),
+        }))
+    );
+    assert_eq!(
+        decode_line(REFUND_TOKEN).unwrap(),
+        Some(Record::Giveaway(GiveawayRecord {
+            event_type: EventType::Refund,
+            giveaway_id: giveaway_id(),
+            amount: 9700000,
+            wallet: pubkey(PAYER),
+            token_mint: Some(pubkey(USDT)),
+        }))
+    );
+}
+
+#[test]
+fn decode_legacy_refund_token_as_receive() {
+    // 迁移前的代币退款日志无法与领取区分，按领取解析
+    assert_eq!(
+        decode_line(LEGACY_REFUND_TOKEN).unwrap(),
+        Some(Record::Giveaway(GiveawayRecord {
+            event_type: EventType::Receive,
+            giveaway_id: giveaway_id(),
+            amount: 9700000,
+            wallet: pubkey(PAYER),
+            token_mint: Some(pubkey(USDT)),
+        }))
+    );
+}
+
+#[test]
+fn decode_cancel_giveaway() {
+    // cancel_giveaway: "4,<id>,<remaining>,<creator>,0"
+    let payload = hex::encode(format!("4,{},5000,{},0", GIVEAWAY_ID, PAYER));
+    assert_eq!(
+        decode_line(&format!("Program log: Giveaway {}", payload)).unwrap(),
+        Some(Record::Giveaway(GiveawayRecord {
+            event_type: EventType::Cancel,
+            giveaway_id: giveaway_id(),
+            amount: 5000,
+            wallet: pubkey(PAYER),
+            token_mint: None,
+        }))
+    );
+}
+
+#[test]
+fn decode_top_up_token_giveaway() {
+    // top_up_token: "5,<id>,<amount>,<creator>,<usdt>"
+    let payload = hex::encode(format!("5,{},2000000,{},{}", GIVEAWAY_ID, PAYER, USDT));
+    assert_eq!(
+        decode_line(&format!("Program log: Giveaway {}", payload)).unwrap(),
+        Some(Record::Giveaway(GiveawayRecord {
+            event_type: EventType::TopUp,
+            giveaway_id: giveaway_id(),
+            amount: 2000000,
+            wallet: pubkey(PAYER),
+            token_mint: Some(pubkey(USDT)),
+        }))
+    );
+}
+
+#[test]
+fn decode_pause_and_resume_giveaway() {
+    // pause_giveaway / resume_giveaway: "6|7,<id>,<remaining>,<creator>,0"
+    for (code, event_type) in [(6, EventType::Pause), (7, EventType::Resume)] {
+        let payload = hex::encode(format!("{},{},5000,{},0", code, GIVEAWAY_ID, PAYER));
+        assert_eq!(
+            decode_line(&format!("Program log: Giveaway {}", payload)).unwrap(),
+            Some(Record::Giveaway(GiveawayRecord {
+                event_type,
+                giveaway_id: giveaway_id(),
+                amount: 5000,
+                wallet: pubkey(PAYER),
+                token_mint: None,
+            }))
+        );
+    }
+}
+
+#[test]
+fn decode_reelpay_payments() {
+    assert_eq!(
+        decode_line(PAY_NATIVE).unwrap(),
+        Some(Record::ReelPay(ReelPayRecord::PayNative {
+            order_id: String::from("ORD20240318001"),
+            amount: 1000000000,
+        }))
+    );
+    assert_eq!(
+        decode_line(PAY_TOKEN).unwrap(),
+        Some(Record::ReelPay(ReelPayRecord::PayToken {
+            order_id: String::from("ORD20240318002"),
+            amount: 2500000,
+            token_mint: pubkey(USDT),
+        }))
+    );
+    assert_eq!(
+        decode_line(PAY_NATIVE_COMMA).unwrap(),
+        Some(Record::ReelPay(ReelPayRecord::PayNative {
+            order_id: String::from("ORD,2024,7"),
+            amount: 1500,
+        }))
+    );
+}
+
+#[test]
+fn decode_transaction_logs() {
+    let logs = [
+        "Program 3Lkno95uimuGtwLXv3oNBhqraJmiaeFMiDakDfo449R4 invoke [1]",
+        "Program log: Instruction: ReceiveNonPutGiveaway",
+        RECEIVE_TOKEN,
+        "Program 3Lkno95uimuGtwLXv3oNBhqraJmiaeFMiDakDfo449R4 success",
+        PAY_NATIVE,
+    ];
+    let records = decode_logs(logs).unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(matches!(records[0], Record::Giveaway(_)));
+    assert!(matches!(records[1], Record::ReelPay(_)));
+}
+
+#[test]
+fn reject_malformed_lines() {
+    assert_eq!(
+        decode_line("Program log: Giveaway zz"),
+        Err(DecodeError::InvalidHex)
+    );
+    // "1,abc"
+    assert_eq!(
+        decode_line("Program log: Giveaway 312c616263"),
+        Err(DecodeError::FieldCount {
+            expected: 5,
+            found: 2
+        })
+    );
+    // "9,<id>,1,<payer>,0"
+    let unknown = hex::encode(format!("9,{},1,{},0", GIVEAWAY_ID, PAYER));
+    assert_eq!(
+        decode_line(&format!("Program log: Giveaway {}", unknown)),
+        Err(DecodeError::UnknownEventType(String::from("9")))
+    );
+    let bad_amount = hex::encode(format!("2,{},-1,{},0", GIVEAWAY_ID, PAYER));
+    assert_eq!(
+        decode_line(&format!("Program log: Giveaway {}", bad_amount)),
+        Err(DecodeError::InvalidNumber(String::from("-1")))
+    );
+    let bad_wallet = hex::encode(format!("2,{},1,not-a-key,0", GIVEAWAY_ID));
+    assert_eq!(
+        decode_line(&format!("Program log: Giveaway {}", bad_wallet)),
+        Err(DecodeError::InvalidPubkey(String::from("not-a-key")))
+    );
+    // "ORD"
+    assert_eq!(
+        decode_line("Program log: ReelPay 4f5244"),
+        Err(DecodeError::FieldCount {
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn ignore_unrelated_lines() {
+    assert_eq!(decode_line("Program log: Instruction: PayNative"), Ok(None));
+    assert_eq!(
+        decode_line("Program log: Skip entry 1: Nonce Already Used"),
+        Ok(None)
+    );
+}
@@ -10,4 +10,36 @@ pub enum GiveawayError {
     Overtime,
     #[msg("Exceed")]
     ExceedError,
+    #[msg("Already Received")]
+    AlreadyReceived,
+    #[msg("All Received")]
+    AllReceived,
+    #[msg("Mint Mismatch")]
+    MintMismatch,
+    #[msg("Not Empty")]
+    NotEmpty,
+    #[msg("Expired")]
+    Expired,
+    #[msg("Not Expired")]
+    NotExpired,
+    #[msg("Cancelled")]
+    Cancelled,
+    #[msg("Mode Mismatch")]
+    ModeMismatch,
+    #[msg("Invalid Proof")]
+    InvalidProof,
+    #[msg("Nonce Already Used")]
+    NonceUsed,
+    #[msg("Invalid Signature")]
+    InvalidSignature,
+    #[msg("Invalid Recovery Id")]
+    InvalidRecoveryId,
+    #[msg("Paused")]
+    Paused,
+    #[msg("Not Paused")]
+    NotPaused,
+    #[msg("Nothing Vested")]
+    NotVested,
+    #[msg("Not Started")]
+    NotStarted,
 }
@@ -2,4 +2,8 @@ pub enum EventType {
     Create = 1,
     Receive = 2,
     Refund = 3,
+    Cancel = 4,
+    TopUp = 5,
+    Pause = 6,
+    Resume = 7,
 }
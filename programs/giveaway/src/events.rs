@@ -0,0 +1,181 @@
+use put_anchor_lang::prelude::*;
+
+use crate::ClaimSigner;
+
+#[cfg(feature = "legacy-log")]
+use crate::{event_type::EventType, utils};
+
+// token_mint 为默认值时表示主币红包
+
+#[event]
+pub struct CreateEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u128,
+    pub giveaway_count: u32,
+    pub start_time: Option<u64>,
+}
+
+#[event]
+pub struct ReceiveEvent {
+    pub giveaway_id: [u8; 20],
+    pub wallet: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u128,
+}
+
+#[event]
+pub struct RefundEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u128,
+}
+
+#[event]
+pub struct TopUpEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u128,
+}
+
+#[event]
+pub struct CancelEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub remaining_amount: u128,
+}
+
+#[event]
+pub struct PauseEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub remaining_amount: u128,
+}
+
+#[event]
+pub struct ResumeEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub remaining_amount: u128,
+}
+
+#[event]
+pub struct BundleAssetAddedEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u128,
+    pub total_amount: u128,
+}
+
+#[event]
+pub struct GiveawayCountUpdatedEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub giveaway_count: u32,
+}
+
+#[event]
+pub struct SignersUpdatedEvent {
+    pub giveaway_id: [u8; 20],
+    pub creator: Pubkey,
+    pub signers: Vec<ClaimSigner>,
+}
+
+// 兼容旧版十六进制日志，迁移完成后移除
+#[cfg(feature = "legacy-log")]
+impl CreateEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::Create,
+            &self.giveaway_id,
+            self.amount,
+            &self.creator,
+            &self.token_mint,
+        );
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl ReceiveEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::Receive,
+            &self.giveaway_id,
+            self.amount,
+            &self.wallet,
+            &self.token_mint,
+        );
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl RefundEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::Refund,
+            &self.giveaway_id,
+            self.amount,
+            &self.creator,
+            &self.token_mint,
+        );
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl TopUpEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::TopUp,
+            &self.giveaway_id,
+            self.amount,
+            &self.creator,
+            &self.token_mint,
+        );
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl CancelEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::Cancel,
+            &self.giveaway_id,
+            self.remaining_amount,
+            &self.creator,
+            &self.token_mint,
+        );
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl PauseEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::Pause,
+            &self.giveaway_id,
+            self.remaining_amount,
+            &self.creator,
+            &self.token_mint,
+        );
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl ResumeEvent {
+    pub fn log(&self) {
+        utils::log_event(
+            EventType::Resume,
+            &self.giveaway_id,
+            self.remaining_amount,
+            &self.creator,
+            &self.token_mint,
+        );
+    }
+}
@@ -1,19 +1,36 @@
 mod errors;
+#[cfg(feature = "legacy-log")]
 mod event_type;
+mod events;
+mod signature;
 mod utils;
 
 use put_anchor_lang::prelude::*;
 use put_anchor_lang::put_program::clock::Clock;
-use put_anchor_lang::put_program::secp256k1_recover::secp256k1_recover;
+use put_anchor_lang::put_program::sysvar::instructions as instructions_sysvar;
+use put_anchor_ppl::associated_token::AssociatedToken;
 use put_anchor_ppl::token::{
-    ppl_token, ppl_token::instruction as token_instruction, Mint, Token, TokenAccount,
+    ppl_token::instruction as token_instruction, Mint, Token, TokenAccount,
 };
-use sha3::{Digest, Keccak256};
 
 use errors::GiveawayError;
+use events::{
+    BundleAssetAddedEvent, CancelEvent, CreateEvent, GiveawayCountUpdatedEvent, PauseEvent,
+    ReceiveEvent, RefundEvent, ResumeEvent, SignersUpdatedEvent, TopUpEvent,
+};
+use signature::ClaimMessage;
 
 declare_id!("3Lkno95uimuGtwLXv3oNBhqraJmiaeFMiDakDfo449R4");
 
+pub const TOKEN_POOL_SEED: &[u8] = b"token_pool";
+pub const CLAIM_RECEIPT_SEED: &[u8] = b"claim_receipt";
+pub const CLAIMER_RECEIPT_SEED: &[u8] = b"claimer_receipt";
+pub const VESTING_SEED: &[u8] = b"vesting";
+// 每个红包最多的领取签名方数量
+pub const MAX_CLAIM_SIGNERS: usize = 3;
+// 组合红包最多的代币种类数量，不含主币
+pub const MAX_BUNDLE_ASSETS: usize = 4;
+
 #[program]
 pub mod giveaway {
 
@@ -25,9 +42,13 @@ pub mod giveaway {
         ctx: Context<CreatePutGiveawayAccounts>,
         args: CreateGiveawayARG,
     ) -> Result<()> {
-        ctx.accounts.giveaway_pool.creator = ctx.accounts.payer.key();
-        ctx.accounts.giveaway_pool.receive_records = Vec::new();
-        ctx.accounts.giveaway_pool.total_amount = args.amount;
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts.giveaway_pool.init(
+            &args,
+            ctx.accounts.payer.key(),
+            Pubkey::default(),
+            current_timestamp,
+        )?;
 
         let transfer_instruction = put_anchor_lang::put_program::system_instruction::transfer(
             ctx.accounts.payer.key,
@@ -44,18 +65,17 @@ pub mod giveaway {
             ],
         )?;
 
-        let giveaway_id_str = hex::encode(args.giveaway_id.to_vec());
-
-        let log_msg = format!(
-            "{},{},{},{},{}",
-            event_type::EventType::Create as u32,
-            giveaway_id_str,
-            args.amount,
-            ctx.accounts.payer.key.to_string(),
-            "0",
-        );
-
-        utils::log(log_msg);
+        let event = CreateEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: Pubkey::default(),
+            amount: args.amount,
+            giveaway_count: args.giveaway_count,
+            start_time: args.start_time,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
         Ok(())
     }
 
@@ -63,9 +83,13 @@ pub mod giveaway {
         ctx: Context<CreateNonPutGiveawayAccounts>,
         args: CreateGiveawayARG,
     ) -> Result<()> {
-        ctx.accounts.giveaway_pool.creator = ctx.accounts.payer.key();
-        ctx.accounts.giveaway_pool.receive_records = Vec::new();
-        ctx.accounts.giveaway_pool.total_amount = args.amount;
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts.giveaway_pool.init(
+            &args,
+            ctx.accounts.payer.key(),
+            ctx.accounts.token_mint.key(),
+            current_timestamp,
+        )?;
 
         let token_tx = token_instruction::transfer(
             ctx.accounts.token_program.key,
@@ -85,18 +109,17 @@ pub mod giveaway {
             ],
         )?;
 
-        let giveaway_id_str = hex::encode(args.giveaway_id.to_vec());
-
-        let log_msg = format!(
-            "{},{},{},{},{}",
-            event_type::EventType::Create as u32,
-            giveaway_id_str,
-            args.amount,
-            ctx.accounts.payer.key.to_string(),
-            ctx.accounts.token_mint.key().to_string(),
-        );
-
-        utils::log(log_msg);
+        let event = CreateEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount: args.amount,
+            giveaway_count: args.giveaway_count,
+            start_time: args.start_time,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
         Ok(())
     }
@@ -105,194 +128,759 @@ pub mod giveaway {
         ctx: Context<ReceivePutGiveawayAccount>,
         args: ReceivePutGiveawayARG,
     ) -> Result<()> {
-        let timestamp_bytes = args.timestamp.to_be_bytes();
-        let amount_bytes = args.amount.to_be_bytes();
+        // 主币红包的币种为默认值
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
         let giveaway_id_bytes = args.giveaway_id;
         let wallet_address_bytes = args.wallet_address;
 
-        // let amount_non_zero_position = amount_bytes.iter().position(|&x| x != 0).unwrap();
-        // let timestamp_non_zero_position = timestamp_bytes.iter().position(|&x| x != 0).unwrap();
-
-        let (pool, _bump) =
-            Pubkey::find_program_address(&[&args.giveaway_id.clone()], ctx.program_id);
-
         // 检查时效性
         let clock = Clock::get()?;
         let current_timestamp = clock.unix_timestamp.unsigned_abs();
 
         require!(args.timestamp > current_timestamp, GiveawayError::Overtime);
-
-        // 检查余额是否充足
+        ctx.accounts
+            .giveaway_pool
+            .check_claimable(current_timestamp)?;
+
+        // 计算领取金额并检查余额是否充足
+        let amount = ctx
+            .accounts
+            .giveaway_pool
+            .claim_amount(args.amount, clock.slot)?;
         require!(
-            args.amount <= ctx.accounts.giveaway_pool.total_amount,
+            amount <= ctx.accounts.giveaway_pool.remaining_amount,
             GiveawayError::ExceedError
         );
 
-        // 校验签名正确
-        let origin_message_bytes = [
-            wallet_address_bytes.as_ref(),
-            giveaway_id_bytes.as_ref(),
-            timestamp_bytes.as_ref(),
-            amount_bytes.as_ref(),
-        ]
-        .concat();
+        // 校验签名正确，金额由程序计算的红包签名中不包含金额
+        let claim = ClaimMessage {
+            wallet: wallet_address_bytes,
+            giveaway_id: giveaway_id_bytes,
+            timestamp: args.timestamp,
+            nonce: args.nonce,
+            index: args.index,
+            amount: ctx
+                .accounts
+                .giveaway_pool
+                .mode
+                .signs_amount()
+                .then_some(amount),
+        };
+
+        signature::verify_claim(
+            &ctx.accounts.giveaway_pool.signers,
+            &ctx.accounts.giveaway_pool.signature_format,
+            &claim,
+            &args.signature,
+            &ctx.accounts.instructions.to_account_info(),
+        )?;
+
+        // 每个 nonce 只能使用一次，使用后创建领取凭证账户
+        utils::create_claim_receipt(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.claim_receipt.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &args.giveaway_id,
+            args.nonce,
+        )?;
 
-        // let prefix_str = String::from("\x19Ethereum Signed Message:\n");
-        // let prefix_bytes = prefix_str.as_bytes();
-        // let message_len = origin_message_bytes.len().to_string(); // 必须是字符串len
-        // let message_bytes = message_len.as_bytes();
-        // let message = [prefix_bytes, message_bytes, origin_message_bytes.as_slice()].concat();
+        // 签名中的钱包必须是领取人
+        require!(
+            ctx.accounts.payer.key().to_bytes() == wallet_address_bytes,
+            GiveawayError::Forbidden
+        );
 
-        let mut hasher_sig = Keccak256::new();
-        hasher_sig.update(origin_message_bytes.as_slice());
-        let sig_message = hasher_sig.finalize();
+        // 每个钱包只能领取一次
+        ctx.accounts
+            .giveaway_pool
+            .record_receive(ctx.accounts.payer.key(), amount, args.index)?;
+        if ctx.accounts.giveaway_pool.record_mode == RecordMode::Receipt {
+            utils::create_claimer_receipt(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.claimer_receipt.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &args.giveaway_id,
+                &ctx.accounts.payer.key(),
+            )?;
+        }
 
-        let signature = &args.signature[0..64];
-        let recover_id = args.signature[64] - 27;
+        utils::transfer_lamports(
+            &ctx.accounts.giveaway_pool.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            amount,
+        )?;
 
-        let secp_pub = secp256k1_recover(&sig_message, recover_id, &signature).unwrap();
+        let event = ReceiveEvent {
+            giveaway_id: args.giveaway_id,
+            wallet: ctx.accounts.payer.key(),
+            token_mint: Pubkey::default(),
+            amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
-        let mut hasher_pub = Keccak256::new();
-        hasher_pub.update(secp_pub.to_bytes());
-        let signed_pub = hasher_pub.finalize();
+        Ok(())
+    }
 
-        let pub_key = &signed_pub[12..];
+    pub fn receive_non_put_giveaway(
+        ctx: Context<ReceiveNonPutGiveawayAccount>,
+        args: ReceiveNonPutGiveawayARG,
+    ) -> Result<()> {
+        let giveaway_id_bytes = args.giveaway_id;
+        let wallet = Pubkey::new_from_array(args.wallet_address);
 
+        // 红包币种必须与创建时一致
         require!(
-            pub_key.eq(giveaway_id_bytes.as_ref()),
-            GiveawayError::Forbidden
+            ctx.accounts.giveaway_pool.token_mint == ctx.accounts.token_mint.key(),
+            GiveawayError::MintMismatch
         );
 
-        let from_account = ctx.accounts.giveaway_pool.to_account_info().lamports();
-        let to_account = ctx.accounts.payer.to_account_info().lamports();
-
-        let final_from_amount = from_account.checked_sub(args.amount);
-        let final_to_amount = to_account.checked_add(args.amount);
+        // 检查时效性
+        let clock = Clock::get()?;
+        let current_timestamp = clock.unix_timestamp.unsigned_abs();
+        require!(args.timestamp > current_timestamp, GiveawayError::Overtime);
+        ctx.accounts
+            .giveaway_pool
+            .check_claimable(current_timestamp)?;
+
+        // 计算领取金额并检查余额是否充足
+        let amount = ctx
+            .accounts
+            .giveaway_pool
+            .claim_amount(args.amount, clock.slot)?;
+        require!(
+            amount <= ctx.accounts.giveaway_pool.remaining_amount,
+            GiveawayError::ExceedError
+        );
 
-        if final_from_amount.is_some() && final_to_amount.is_some() {
-            **ctx
+        // 校验签名正确，金额由程序计算的红包签名中不包含金额
+        let claim = ClaimMessage {
+            wallet: args.wallet_address,
+            giveaway_id: giveaway_id_bytes,
+            timestamp: args.timestamp,
+            nonce: args.nonce,
+            index: args.index,
+            amount: ctx
                 .accounts
                 .giveaway_pool
-                .to_account_info()
-                .lamports
-                .borrow_mut() = final_from_amount.unwrap();
+                .mode
+                .signs_amount()
+                .then_some(amount),
+        };
+
+        signature::verify_claim(
+            &ctx.accounts.giveaway_pool.signers,
+            &ctx.accounts.giveaway_pool.signature_format,
+            &claim,
+            &args.signature,
+            &ctx.accounts.instructions.to_account_info(),
+        )?;
 
-            **ctx.accounts.payer.to_account_info().lamports.borrow_mut() = final_to_amount.unwrap();
+        // 每个 nonce 只能使用一次，使用后创建领取凭证账户
+        utils::create_claim_receipt(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.claim_receipt.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &args.giveaway_id,
+            args.nonce,
+        )?;
+
+        // 每个钱包只能领取一次
+        ctx.accounts
+            .giveaway_pool
+            .record_receive(wallet, amount, args.index)?;
+        if ctx.accounts.giveaway_pool.record_mode == RecordMode::Receipt {
+            utils::create_claimer_receipt(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.claimer_receipt.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &args.giveaway_id,
+                &wallet,
+            )?;
         }
 
-        let giveaway_id_str = hex::encode(args.giveaway_id.to_vec());
+        utils::transfer_from_token_pool(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.token_pool.to_account_info(),
+            &ctx.accounts.to_account.to_account_info(),
+            &args.giveaway_id,
+            &ctx.accounts.token_mint.key(),
+            amount,
+        )?;
 
-        let log_msg = format!(
-            "{},{},{},{},{}",
-            event_type::EventType::Receive as u32,
-            giveaway_id_str,
-            args.amount,
-            ctx.accounts.payer.key.to_string(),
-            "0",
+        let event = ReceiveEvent {
+            giveaway_id: args.giveaway_id,
+            wallet,
+            token_mint: ctx.accounts.token_mint.key(),
+            amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+        Ok(())
+    }
+
+    // 批量领取主币红包，remaining_accounts 按 [领取钱包, 领取凭证] 依次传入，
+    // 凭证模式下每条再加上领取人凭证
+    // skip_invalid 为 true 时跳过校验失败的领取，否则任意一条失败整笔交易回滚
+    pub fn batch_receive_put_giveaway<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchReceivePutGiveawayAccount<'info>>,
+        args: BatchReceivePutGiveawayARG,
+    ) -> Result<()> {
+        // 主币红包的币种为默认值
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
+        let stride = ctx.accounts.giveaway_pool.record_mode.batch_accounts();
+        require!(
+            !args.entries.is_empty() && ctx.remaining_accounts.len() == args.entries.len() * stride,
+            GiveawayError::ArgsError
         );
 
-        utils::log(log_msg);
+        let clock = Clock::get()?;
+        let current_timestamp = clock.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .check_claimable(current_timestamp)?;
+
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let pool_info = ctx.accounts.giveaway_pool.to_account_info();
+        let instructions = ctx.accounts.instructions.to_account_info();
+
+        for (index, (entry, accounts)) in args
+            .entries
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(stride))
+            .enumerate()
+        {
+            let (wallet, claim_receipt, claimer_receipt) =
+                (&accounts[0], &accounts[1], accounts.get(2));
+            let amount = match receive_batch_entry(
+                &mut ctx.accounts.giveaway_pool,
+                &args.giveaway_id,
+                entry,
+                accounts,
+                &clock,
+                &instructions,
+            ) {
+                Ok(amount) => amount,
+                Err(error) if args.skip_invalid => {
+                    // 不能以 "Giveaway " 开头，否则会被日志解析当作事件
+                    msg!("Skip entry {}: {}", index, error);
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            utils::create_claim_receipt(
+                &payer,
+                claim_receipt,
+                &system_program,
+                &args.giveaway_id,
+                entry.nonce,
+            )?;
+            if let Some(claimer_receipt) = claimer_receipt {
+                utils::create_claimer_receipt(
+                    &payer,
+                    claimer_receipt,
+                    &system_program,
+                    &args.giveaway_id,
+                    wallet.key,
+                )?;
+            }
+            utils::transfer_lamports(&pool_info, wallet, amount)?;
+
+            let event = ReceiveEvent {
+                giveaway_id: args.giveaway_id,
+                wallet: wallet.key(),
+                token_mint: Pubkey::default(),
+                amount,
+            };
+            #[cfg(feature = "legacy-log")]
+            event.log();
+            emit!(event);
+        }
 
         Ok(())
     }
 
-    pub fn receive_non_put_giveaway(
-        ctx: Context<ReceiveNonPutGiveawayAccount>,
-        args: ReceiveNonPutGiveawayARG,
+    // 给组合红包加入一种代币，按每份数量乘以红包个数转入该代币的代币池，开始领取后不能再加入
+    pub fn add_bundle_asset(
+        ctx: Context<AddBundleAssetAccount>,
+        args: AddBundleAssetARGS,
     ) -> Result<()> {
-        let timestamp_bytes = args.timestamp.to_be_bytes();
-        let amount_bytes = args.amount.to_be_bytes();
-        let giveaway_id_bytes = args.giveaway_id;
-        let to_account_bytes = ctx.accounts.to_account.key().to_bytes();
-        let mint_address = ctx.accounts.token_mint.key().to_bytes();
-
-        // let amount_non_zero_position = amount_bytes.iter().position(|&x| x != 0).unwrap();
-        // let timestamp_non_zero_position = timestamp_bytes.iter().position(|&x| x != 0).unwrap();
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        let total_amount = ctx.accounts.giveaway_pool.add_bundle_asset(
+            ctx.accounts.token_mint.key(),
+            args.amount,
+            current_timestamp,
+        )?;
 
-        let (_pool, _bump) = Pubkey::find_program_address(
+        let token_tx = token_instruction::transfer(
+            ctx.accounts.token_program.key,
+            &ctx.accounts.from_account.key(),
+            &ctx.accounts.token_pool.key(),
+            ctx.accounts.payer.key,
+            &[],
+            total_amount,
+        )?;
+        invoke(
+            &token_tx,
             &[
-                &ctx.accounts.payer.key().to_bytes(),
-                &ctx.accounts.token_mint.key().to_bytes(),
+                ctx.accounts.from_account.to_account_info(),
+                ctx.accounts.token_pool.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
             ],
-            ctx.program_id,
-        );
+        )?;
+
+        emit!(BundleAssetAddedEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount: args.amount,
+            total_amount,
+        });
+
+        Ok(())
+    }
 
+    // 领取组合红包，一次领取主币和全部代币各一份
+    // remaining_accounts 按加入顺序每种代币依次传入 [代币池, 领取人关联代币账户, 代币]
+    pub fn receive_bundle_giveaway<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReceiveBundleGiveawayAccount<'info>>,
+        args: ReceiveBundleGiveawayARG,
+    ) -> Result<()> {
         // 检查时效性
-        let clock = Clock::get()?;
-        let current_timestamp = clock.unix_timestamp.unsigned_abs();
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
         require!(args.timestamp > current_timestamp, GiveawayError::Overtime);
+        ctx.accounts
+            .giveaway_pool
+            .check_claimable(current_timestamp)?;
 
-        // 检查余额是否充足
+        let amount = ctx.accounts.giveaway_pool.bundle_amount()?;
         require!(
-            args.amount <= ctx.accounts.giveaway_pool.total_amount,
-            GiveawayError::ExceedError
+            ctx.remaining_accounts.len() == ctx.accounts.giveaway_pool.bundle.len() * 3,
+            GiveawayError::ArgsError
+        );
+
+        // 组合红包每份数量固定，签名中不包含金额
+        let claim = ClaimMessage {
+            wallet: args.wallet_address,
+            giveaway_id: args.giveaway_id,
+            timestamp: args.timestamp,
+            nonce: args.nonce,
+            index: args.index,
+            amount: None,
+        };
+        signature::verify_claim(
+            &ctx.accounts.giveaway_pool.signers,
+            &ctx.accounts.giveaway_pool.signature_format,
+            &claim,
+            &args.signature,
+            &ctx.accounts.instructions.to_account_info(),
+        )?;
+
+        utils::create_claim_receipt(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.claim_receipt.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &args.giveaway_id,
+            args.nonce,
+        )?;
+
+        // 签名中的钱包必须是领取人
+        require!(
+            ctx.accounts.payer.key().to_bytes() == args.wallet_address,
+            GiveawayError::Forbidden
+        );
+
+        ctx.accounts
+            .giveaway_pool
+            .record_receive(ctx.accounts.payer.key(), amount, args.index)?;
+        if ctx.accounts.giveaway_pool.record_mode == RecordMode::Receipt {
+            utils::create_claimer_receipt(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.claimer_receipt.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &args.giveaway_id,
+                &ctx.accounts.payer.key(),
+            )?;
+        }
+
+        if amount > 0 {
+            utils::transfer_lamports(
+                &ctx.accounts.giveaway_pool.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                amount,
+            )?;
+
+            let event = ReceiveEvent {
+                giveaway_id: args.giveaway_id,
+                wallet: ctx.accounts.payer.key(),
+                token_mint: Pubkey::default(),
+                amount,
+            };
+            #[cfg(feature = "legacy-log")]
+            event.log();
+            emit!(event);
+        }
+
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+        for (asset, accounts) in ctx
+            .accounts
+            .giveaway_pool
+            .bundle
+            .iter_mut()
+            .zip(ctx.remaining_accounts.chunks(3))
+        {
+            let (token_pool, to_account, token_mint) = (&accounts[0], &accounts[1], &accounts[2]);
+            require!(
+                token_mint.key() == asset.token_mint,
+                GiveawayError::MintMismatch
+            );
+            utils::create_associated_token_account(
+                &payer,
+                &payer,
+                to_account,
+                token_mint,
+                &system_program,
+                &token_program,
+                &associated_token_program,
+            )?;
+            check_bundle_accounts(&args.giveaway_id, asset, token_pool, to_account)?;
+
+            asset.remaining_amount = asset
+                .remaining_amount
+                .checked_sub(asset.amount)
+                .ok_or(GiveawayError::ExceedError)?;
+            utils::transfer_from_token_pool(
+                &token_program,
+                token_pool,
+                to_account,
+                &args.giveaway_id,
+                &asset.token_mint,
+                asset.amount,
+            )?;
+
+            let event = ReceiveEvent {
+                giveaway_id: args.giveaway_id,
+                wallet: ctx.accounts.payer.key(),
+                token_mint: asset.token_mint,
+                amount: asset.amount,
+            };
+            #[cfg(feature = "legacy-log")]
+            event.log();
+            emit!(event);
+        }
+
+        Ok(())
+    }
+
+    // 白名单红包：凭 Merkle 证明领取，无需签名服务在线
+    pub fn receive_put_giveaway_with_proof(
+        ctx: Context<ReceivePutProofAccount>,
+        args: ReceiveProofARG,
+    ) -> Result<()> {
+        // 主币红包的币种为默认值
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .check_claimable(current_timestamp)?;
+
+        ctx.accounts.giveaway_pool.verify_proof(
+            &ctx.accounts.payer.key(),
+            args.amount,
+            args.index,
+            &args.proof,
+        )?;
+
+        // 每个钱包只能领取一次
+        ctx.accounts.giveaway_pool.record_receive(
+            ctx.accounts.payer.key(),
+            args.amount,
+            args.index,
+        )?;
+        if ctx.accounts.giveaway_pool.record_mode == RecordMode::Receipt {
+            utils::create_claimer_receipt(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.claimer_receipt.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &args.giveaway_id,
+                &ctx.accounts.payer.key(),
+            )?;
+        }
+
+        utils::transfer_lamports(
+            &ctx.accounts.giveaway_pool.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            args.amount,
+        )?;
+
+        let event = ReceiveEvent {
+            giveaway_id: args.giveaway_id,
+            wallet: ctx.accounts.payer.key(),
+            token_mint: Pubkey::default(),
+            amount: args.amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
+
+    pub fn receive_non_put_giveaway_with_proof(
+        ctx: Context<ReceiveNonPutProofAccount>,
+        args: ReceiveProofARG,
+    ) -> Result<()> {
+        // 红包币种必须与创建时一致
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == ctx.accounts.token_mint.key(),
+            GiveawayError::MintMismatch
+        );
+
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .check_claimable(current_timestamp)?;
+
+        ctx.accounts.giveaway_pool.verify_proof(
+            &ctx.accounts.payer.key(),
+            args.amount,
+            args.index,
+            &args.proof,
+        )?;
+
+        // 每个钱包只能领取一次
+        ctx.accounts.giveaway_pool.record_receive(
+            ctx.accounts.payer.key(),
+            args.amount,
+            args.index,
+        )?;
+        if ctx.accounts.giveaway_pool.record_mode == RecordMode::Receipt {
+            utils::create_claimer_receipt(
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.claimer_receipt.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &args.giveaway_id,
+                &ctx.accounts.payer.key(),
+            )?;
+        }
+
+        utils::transfer_from_token_pool(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.token_pool.to_account_info(),
+            &ctx.accounts.to_account.to_account_info(),
+            &args.giveaway_id,
+            &ctx.accounts.token_mint.key(),
+            args.amount,
+        )?;
+
+        let event = ReceiveEvent {
+            giveaway_id: args.giveaway_id,
+            wallet: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount: args.amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
+
+    // 领取线性释放红包中已释放未领取的部分，首次领取时凭签名或 Merkle 证明登记分配额度
+    pub fn claim_vested_put(
+        ctx: Context<ClaimVestedPutAccount>,
+        args: ClaimVestedARG,
+    ) -> Result<()> {
+        // 主币红包的币种为默认值
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
+        let amount = withdraw_vested(
+            &mut ctx.accounts.giveaway_pool,
+            &mut ctx.accounts.vesting_account,
+            &ctx.accounts.payer.key(),
+            &args,
+            &ctx.accounts.instructions.to_account_info(),
+        )?;
+        // 只登记了额度，尚无可领取的金额
+        if amount == 0 {
+            return Ok(());
+        }
+
+        utils::transfer_lamports(
+            &ctx.accounts.giveaway_pool.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            amount,
+        )?;
+
+        let event = ReceiveEvent {
+            giveaway_id: args.giveaway_id,
+            wallet: ctx.accounts.payer.key(),
+            token_mint: Pubkey::default(),
+            amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
+
+    pub fn claim_vested_token(
+        ctx: Context<ClaimVestedTokenAccount>,
+        args: ClaimVestedARG,
+    ) -> Result<()> {
+        // 红包币种必须与创建时一致
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == ctx.accounts.token_mint.key(),
+            GiveawayError::MintMismatch
         );
 
-        // 校验签名正确
-        let origin_message_bytes = [
-            ctx.accounts.payer.key().as_ref(),
-            giveaway_id_bytes.as_ref(),
-            timestamp_bytes.as_ref(),
-            amount_bytes.as_ref(),
-        ]
-        .concat();
+        let amount = withdraw_vested(
+            &mut ctx.accounts.giveaway_pool,
+            &mut ctx.accounts.vesting_account,
+            &ctx.accounts.payer.key(),
+            &args,
+            &ctx.accounts.instructions.to_account_info(),
+        )?;
+        // 只登记了额度，尚无可领取的金额
+        if amount == 0 {
+            return Ok(());
+        }
+
+        utils::transfer_from_token_pool(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.token_pool.to_account_info(),
+            &ctx.accounts.to_account.to_account_info(),
+            &args.giveaway_id,
+            &ctx.accounts.token_mint.key(),
+            amount,
+        )?;
+
+        let event = ReceiveEvent {
+            giveaway_id: args.giveaway_id,
+            wallet: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
 
-        let mut hash_origin_message: sha3::digest::core_api::CoreWrapper<sha3::Keccak256Core> =
-            Keccak256::new();
-        hash_origin_message.update(origin_message_bytes.as_slice());
-        let hashed_origin_message = hash_origin_message.finalize();
+    // 给已有主币红包追加金额，只有创建者可以追加
+    pub fn top_up_put(
+        ctx: Context<TopUpPutGiveawayAccount>,
+        args: TopUpGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .top_up(args.amount, current_timestamp)?;
 
-        let signature = &args.signature[0..64];
-        let recover_id = args.signature[64] - 27;
+        let transfer_instruction = put_anchor_lang::put_program::system_instruction::transfer(
+            ctx.accounts.payer.key,
+            &ctx.accounts.giveaway_pool.key(),
+            args.amount,
+        );
+        invoke(
+            &transfer_instruction,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.giveaway_pool.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
 
-        let secp_pub =
-            secp256k1_recover(hashed_origin_message.as_slice(), recover_id, &signature).unwrap();
-        secp_pub.to_bytes();
+        let event = TopUpEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: Pubkey::default(),
+            amount: args.amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
-        let mut hasher_pub = Keccak256::new();
-        hasher_pub.update(secp_pub.to_bytes());
-        let signed_pub = hasher_pub.finalize();
-        let pub_key = &signed_pub[12..];
+        Ok(())
+    }
 
+    // 给已有代币红包追加金额，只有创建者可以追加
+    pub fn top_up_token(
+        ctx: Context<TopUpTokenGiveawayAccount>,
+        args: TopUpGiveawayARGS,
+    ) -> Result<()> {
         require!(
-            pub_key.eq(giveaway_id_bytes.as_ref()),
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
             GiveawayError::Forbidden
         );
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == ctx.accounts.token_mint.key(),
+            GiveawayError::MintMismatch
+        );
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .top_up(args.amount, current_timestamp)?;
 
-        let transfer_instruction = ppl_token::instruction::transfer(
+        let token_tx = token_instruction::transfer(
             ctx.accounts.token_program.key,
+            &ctx.accounts.from_account.key(),
             &ctx.accounts.token_pool.key(),
-            &ctx.accounts.to_account.key(),
-            &ctx.accounts.token_pool.key(),
-            &[&ctx.accounts.token_pool.key()],
+            ctx.accounts.payer.key,
+            &[],
             args.amount,
         )?;
-        put_anchor_lang::put_program::program::invoke_signed(
-            &transfer_instruction,
+        invoke(
+            &token_tx,
             &[
-                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.from_account.to_account_info(),
                 ctx.accounts.token_pool.to_account_info(),
-                ctx.accounts.to_account.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
             ],
-            &[&[
-                ctx.accounts.payer.key.as_ref(),
-                ctx.accounts.token_mint.key().as_ref(),
-                &[_bump],
-            ]],
         )?;
 
-        let giveaway_id_str = hex::encode(args.giveaway_id.to_vec());
-
-        let log_msg = format!(
-            "{},{},{},{},{}",
-            event_type::EventType::Receive as u32,
-            giveaway_id_str,
-            args.amount,
-            ctx.accounts.payer.key.to_string(),
-            ctx.accounts.token_mint.key().to_string(),
-        );
+        let event = TopUpEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount: args.amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
-        utils::log(log_msg);
         Ok(())
     }
 
@@ -308,39 +896,38 @@ pub mod giveaway {
                 .eq(ctx.accounts.giveaway_pool.creator.key().as_ref()),
             GiveawayError::Forbidden
         );
+        // 主币红包的币种为默认值
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
 
-        let remain_fund = ctx.accounts.giveaway_pool.total_amount;
-        ctx.accounts.giveaway_pool.total_amount = 0;
-
-        let from_account = ctx.accounts.giveaway_pool.to_account_info().lamports();
-        let to_account = ctx.accounts.payer.to_account_info().lamports();
-
-        let final_from_amount = from_account.checked_sub(remain_fund);
-        let final_to_amount = to_account.checked_add(remain_fund);
-
-        if final_from_amount.is_some() && final_to_amount.is_some() {
-            **ctx
-                .accounts
-                .giveaway_pool
-                .to_account_info()
-                .lamports
-                .borrow_mut() = final_from_amount.unwrap();
-
-            **ctx.accounts.payer.to_account_info().lamports.borrow_mut() = final_to_amount.unwrap();
-        }
+        // 有过期时间的红包，过期或取消后才能退款
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .check_refundable(current_timestamp)?;
 
-        let giveaway_id_str = hex::encode(args.giveaway_id.to_vec());
+        let remain_fund = ctx
+            .accounts
+            .giveaway_pool
+            .take_remaining(current_timestamp)?;
 
-        let log_msg = format!(
-            "{},{},{},{},{}",
-            event_type::EventType::Refund as u32,
-            giveaway_id_str,
+        utils::transfer_lamports(
+            &ctx.accounts.giveaway_pool.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
             remain_fund,
-            ctx.accounts.payer.key.to_string(),
-            "0",
-        );
+        )?;
 
-        utils::log(log_msg);
+        let event = RefundEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: Pubkey::default(),
+            amount: remain_fund,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
         Ok(())
     }
@@ -358,72 +945,606 @@ pub mod giveaway {
             GiveawayError::Forbidden
         );
 
-        let (_pool, _bump) = Pubkey::find_program_address(
-            &[
-                &ctx.accounts.payer.key().to_bytes(),
-                &ctx.accounts.token_mint.key().to_bytes(),
-            ],
-            ctx.program_id,
+        // 红包币种必须与创建时一致
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == ctx.accounts.token_mint.key(),
+            GiveawayError::MintMismatch
         );
 
-        let remain_amount = ctx.accounts.giveaway_pool.total_amount;
-
-        let transfer_instruction = ppl_token::instruction::transfer(
-            ctx.accounts.token_program.key,
-            &ctx.accounts.token_pool.key(),
-            &ctx.accounts.to_account.key(),
-            &ctx.accounts.token_pool.key(),
-            &[&ctx.accounts.token_pool.key()],
+        // 有过期时间的红包，过期或取消后才能退款
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .check_refundable(current_timestamp)?;
+
+        let remain_amount = ctx
+            .accounts
+            .giveaway_pool
+            .take_remaining(current_timestamp)?;
+
+        utils::transfer_from_token_pool(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.token_pool.to_account_info(),
+            &ctx.accounts.to_account.to_account_info(),
+            &args.giveaway_id,
+            &ctx.accounts.token_mint.key(),
             remain_amount,
         )?;
-        put_anchor_lang::put_program::program::invoke_signed(
-            &transfer_instruction,
-            &[
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.token_pool.to_account_info(),
-                ctx.accounts.to_account.to_account_info(),
-            ],
-            &[&[
-                ctx.accounts.payer.key.as_ref(),
-                ctx.accounts.token_mint.key().as_ref(),
-                &[_bump],
-            ]],
-        )?;
 
-        let giveaway_id_str = hex::encode(args.giveaway_id.to_vec());
+        let event = RefundEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            amount: remain_amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
-        let log_msg = format!(
-            "{},{},{},{},{}",
-            event_type::EventType::Receive as u32,
-            giveaway_id_str,
-            remain_amount,
-            ctx.accounts.payer.key.to_string(),
-            ctx.accounts.token_mint.key().to_string(),
+        Ok(())
+    }
+
+    // 组合红包退款，一次退还主币和全部代币的剩余金额
+    // remaining_accounts 按加入顺序每种代币依次传入 [代币池, 创建者代币账户]
+    pub fn refund_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundBundleGiveawayAccount<'info>>,
+        args: RefundPutGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
         );
+        ctx.accounts.giveaway_pool.bundle_amount()?;
+        require!(
+            ctx.remaining_accounts.len() == ctx.accounts.giveaway_pool.bundle.len() * 2,
+            GiveawayError::ArgsError
+        );
+
+        // 有过期时间的红包，过期或取消后才能退款
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts
+            .giveaway_pool
+            .check_refundable(current_timestamp)?;
+
+        let remain_fund = ctx
+            .accounts
+            .giveaway_pool
+            .take_remaining(current_timestamp)?;
+        if remain_fund > 0 {
+            utils::transfer_lamports(
+                &ctx.accounts.giveaway_pool.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                remain_fund,
+            )?;
+
+            let event = RefundEvent {
+                giveaway_id: args.giveaway_id,
+                creator: ctx.accounts.payer.key(),
+                token_mint: Pubkey::default(),
+                amount: remain_fund,
+            };
+            #[cfg(feature = "legacy-log")]
+            event.log();
+            emit!(event);
+        }
 
-        utils::log(log_msg);
+        let token_program = ctx.accounts.token_program.to_account_info();
+        for (asset, accounts) in ctx
+            .accounts
+            .giveaway_pool
+            .bundle
+            .iter_mut()
+            .zip(ctx.remaining_accounts.chunks(2))
+        {
+            let (token_pool, to_account) = (&accounts[0], &accounts[1]);
+            check_bundle_accounts(&args.giveaway_id, asset, token_pool, to_account)?;
+
+            let remain_amount = asset.remaining_amount;
+            if remain_amount == 0 {
+                continue;
+            }
+            asset.remaining_amount = 0;
+            utils::transfer_from_token_pool(
+                &token_program,
+                token_pool,
+                to_account,
+                &args.giveaway_id,
+                &asset.token_mint,
+                remain_amount,
+            )?;
+
+            let event = RefundEvent {
+                giveaway_id: args.giveaway_id,
+                creator: ctx.accounts.payer.key(),
+                token_mint: asset.token_mint,
+                amount: remain_amount,
+            };
+            #[cfg(feature = "legacy-log")]
+            event.log();
+            emit!(event);
+        }
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(args: CreateGiveawayARG)]
-pub struct CreateNonPutGiveawayAccounts<'info> {
-    system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
-    rent: Sysvar<'info, Rent>,
-    #[account(mut)]
-    payer: Signer<'info>,
-    /// CHECK:
-    #[account(mut, token::mint = token_mint)]
-    from_account: Account<'info, TokenAccount>,
-    /// CHECK:
-    #[account(init_if_needed, payer = payer, token::mint = token_mint, token::authority = token_pool, seeds = [&payer.key().to_bytes(), &token_mint.key().to_bytes()], bump)]
-    token_pool: Account<'info, TokenAccount>,
-    token_mint: Account<'info, Mint>,
+    // 创建者取消红包，取消后不能再领取，可立即退款
+    pub fn cancel_giveaway(
+        ctx: Context<CancelGiveawayAccount>,
+        args: CancelGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            ctx.accounts.giveaway_pool.status != GiveawayStatus::Cancelled,
+            GiveawayError::Cancelled
+        );
+
+        ctx.accounts.giveaway_pool.status = GiveawayStatus::Cancelled;
+        // 线性释放红包取消后停止释放，已释放的部分受益人仍可领取
+        if ctx.accounts.giveaway_pool.mode.is_vesting() {
+            let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+            ctx.accounts.giveaway_pool.expire_time = Some(
+                ctx.accounts
+                    .giveaway_pool
+                    .expire_time
+                    .map_or(current_timestamp, |expire_time| {
+                        expire_time.min(current_timestamp)
+                    }),
+            );
+        }
+
+        let event = CancelEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.giveaway_pool.token_mint,
+            remaining_amount: ctx.accounts.giveaway_pool.remaining_amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
+
+    // 提高红包个数，领取记录和位图随账户扩容，新增租金由创建者支付
+    pub fn set_giveaway_count(
+        ctx: Context<SetGiveawayCountAccount>,
+        args: SetGiveawayCountARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            args.giveaway_count > ctx.accounts.giveaway_pool.giveaway_count,
+            GiveawayError::ArgsError
+        );
+        // 已取消或已过期的红包不能再扩容
+        let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+        ctx.accounts.giveaway_pool.check_open(current_timestamp)?;
+        // 均分和拼手气红包按总金额和个数计算每份金额，已有人领取后增加个数会让后来的人领不到
+        require!(
+            ctx.accounts.giveaway_pool.claimed_count == 0
+                || !matches!(
+                    ctx.accounts.giveaway_pool.mode,
+                    GiveawayMode::Equal | GiveawayMode::Lucky { .. }
+                ),
+            GiveawayError::ArgsError
+        );
+        ctx.accounts
+            .giveaway_pool
+            .mode
+            .validate(ctx.accounts.giveaway_pool.total_amount, args.giveaway_count)?;
+
+        let bitmap_len = ctx
+            .accounts
+            .giveaway_pool
+            .record_mode
+            .bitmap_len(args.giveaway_count);
+        ctx.accounts.giveaway_pool.giveaway_count = args.giveaway_count;
+        ctx.accounts
+            .giveaway_pool
+            .claimed_bitmap
+            .resize(bitmap_len, 0);
+
+        emit!(GiveawayCountUpdatedEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            giveaway_count: args.giveaway_count,
+        });
+
+        Ok(())
+    }
+
+    // 更换领取签名方，已签发但未领取的旧签名随之失效
+    pub fn set_signers(ctx: Context<SetSignersAccount>, args: SetSignersARGS) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        ctx.accounts
+            .giveaway_pool
+            .signature_format
+            .validate(&args.signers, &ctx.accounts.giveaway_pool.mode)?;
+
+        ctx.accounts.giveaway_pool.signers = args.signers.clone();
+
+        emit!(SignersUpdatedEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            signers: args.signers,
+        });
+
+        Ok(())
+    }
+
+    // 暂停领取，恢复前所有领取都会被拒绝
+    pub fn pause_giveaway(
+        ctx: Context<PauseGiveawayAccount>,
+        args: PauseGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            ctx.accounts.giveaway_pool.status != GiveawayStatus::Cancelled,
+            GiveawayError::Cancelled
+        );
+        require!(
+            ctx.accounts.giveaway_pool.status != GiveawayStatus::Paused,
+            GiveawayError::Paused
+        );
+
+        ctx.accounts.giveaway_pool.status = GiveawayStatus::Paused;
+
+        let event = PauseEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.giveaway_pool.token_mint,
+            remaining_amount: ctx.accounts.giveaway_pool.remaining_amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
+
+    pub fn resume_giveaway(
+        ctx: Context<PauseGiveawayAccount>,
+        args: PauseGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            ctx.accounts.giveaway_pool.status == GiveawayStatus::Paused,
+            GiveawayError::NotPaused
+        );
+
+        ctx.accounts.giveaway_pool.status = GiveawayStatus::Active;
+
+        let event = ResumeEvent {
+            giveaway_id: args.giveaway_id,
+            creator: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.giveaway_pool.token_mint,
+            remaining_amount: ctx.accounts.giveaway_pool.remaining_amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
+
+        Ok(())
+    }
+
+    // 关闭已领完或已退款的主币红包，租金退还创建者
+    pub fn close_put(
+        ctx: Context<ClosePutGiveawayAccount>,
+        _args: CloseGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            ctx.accounts.giveaway_pool.remaining_amount == 0,
+            GiveawayError::NotEmpty
+        );
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == Pubkey::default(),
+            GiveawayError::MintMismatch
+        );
+        // 组合红包需要同时关闭代币池，由 close_bundle 关闭
+        require!(
+            !ctx.accounts.giveaway_pool.mode.is_bundle(),
+            GiveawayError::ModeMismatch
+        );
+
+        // giveaway_pool 账户由 close 约束关闭
+        Ok(())
+    }
 
-    #[account(init_if_needed, payer = payer, space = 32 + 8 + 16 + usize::try_from(args.giveaway_count).unwrap() * 32, seeds = [&args.giveaway_id.clone()], bump)]
+    // 关闭已领完或已退款的代币红包及其代币池，租金退还创建者
+    pub fn close_token(
+        ctx: Context<CloseTokenGiveawayAccount>,
+        args: CloseGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        require!(
+            ctx.accounts.giveaway_pool.remaining_amount == 0,
+            GiveawayError::NotEmpty
+        );
+        require!(
+            ctx.accounts.giveaway_pool.token_mint == ctx.accounts.token_mint.key(),
+            GiveawayError::MintMismatch
+        );
+
+        // 代币池中不属于红包余额的代币（如直接转入的）一并退还，否则无法关闭
+        let dust = ctx.accounts.token_pool.amount;
+        if dust > 0 {
+            utils::transfer_from_token_pool(
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.token_pool.to_account_info(),
+                &ctx.accounts.to_account.to_account_info(),
+                &args.giveaway_id,
+                &ctx.accounts.token_mint.key(),
+                dust,
+            )?;
+        }
+
+        utils::close_token_pool(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.token_pool.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &args.giveaway_id,
+            &ctx.accounts.token_mint.key(),
+        )?;
+
+        // giveaway_pool 账户由 close 约束关闭
+        Ok(())
+    }
+
+    // 关闭已领完或已退款的组合红包及其全部代币池，租金退还创建者
+    // remaining_accounts 按加入顺序每种代币依次传入 [代币池, 创建者代币账户]
+    pub fn close_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseBundleGiveawayAccount<'info>>,
+        args: CloseGiveawayARGS,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.giveaway_pool.creator,
+            GiveawayError::Forbidden
+        );
+        ctx.accounts.giveaway_pool.bundle_amount()?;
+        require!(
+            ctx.accounts.giveaway_pool.remaining_amount == 0,
+            GiveawayError::NotEmpty
+        );
+        require!(
+            ctx.remaining_accounts.len() == ctx.accounts.giveaway_pool.bundle.len() * 2,
+            GiveawayError::ArgsError
+        );
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        for (asset, accounts) in ctx
+            .accounts
+            .giveaway_pool
+            .bundle
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(2))
+        {
+            require!(asset.remaining_amount == 0, GiveawayError::NotEmpty);
+            let (token_pool, to_account) = (&accounts[0], &accounts[1]);
+            check_bundle_accounts(&args.giveaway_id, asset, token_pool, to_account)?;
+
+            // 代币池中不属于红包余额的代币（如直接转入的）一并退还，否则无法关闭
+            let dust =
+                TokenAccount::try_deserialize(&mut &token_pool.try_borrow_data()?[..])?.amount;
+            if dust > 0 {
+                utils::transfer_from_token_pool(
+                    &token_program,
+                    token_pool,
+                    to_account,
+                    &args.giveaway_id,
+                    &asset.token_mint,
+                    dust,
+                )?;
+            }
+
+            utils::close_token_pool(
+                &token_program,
+                token_pool,
+                &payer,
+                &args.giveaway_id,
+                &asset.token_mint,
+            )?;
+        }
+
+        // giveaway_pool 账户由 close 约束关闭
+        Ok(())
+    }
+}
+
+// 检查组合红包 remaining_accounts 中的代币池和收款代币账户与代币种类一致
+fn check_bundle_accounts(
+    giveaway_id: &[u8; 20],
+    asset: &BundleAsset,
+    token_pool: &AccountInfo,
+    to_account: &AccountInfo,
+) -> Result<()> {
+    let (pool, _bump) = utils::token_pool_signer(giveaway_id, &asset.token_mint);
+    require!(token_pool.key() == pool, GiveawayError::MintMismatch);
+    require!(
+        to_account.owner == &Token::id(),
+        GiveawayError::MintMismatch
+    );
+    let to_account = TokenAccount::try_deserialize(&mut &to_account.try_borrow_data()?[..])?;
+    require!(
+        to_account.mint == asset.token_mint,
+        GiveawayError::MintMismatch
+    );
+    Ok(())
+}
+
+// 计算受益人本次可领取的金额并记账，首次领取时校验并登记分配额度
+fn withdraw_vested(
+    giveaway_pool: &mut GiveawayPool,
+    vesting_account: &mut VestingAccount,
+    beneficiary: &Pubkey,
+    args: &ClaimVestedARG,
+    instructions: &AccountInfo,
+) -> Result<u128> {
+    let current_timestamp = Clock::get()?.unix_timestamp.unsigned_abs();
+
+    let registering = vesting_account.beneficiary == Pubkey::default();
+    if registering {
+        giveaway_pool.check_claimable(current_timestamp)?;
+        let proof = args.proof.as_ref().ok_or(GiveawayError::ArgsError)?;
+        giveaway_pool.verify_allocation(
+            &args.giveaway_id,
+            beneficiary,
+            args.allocation,
+            proof,
+            current_timestamp,
+            instructions,
+        )?;
+        require!(
+            giveaway_pool.claimed_count < giveaway_pool.giveaway_count,
+            GiveawayError::AllReceived
+        );
+        // 已登记未领取的额度不能超过剩余金额，退款时会保留这部分
+        let allocated_amount = giveaway_pool
+            .allocated_amount
+            .checked_add(args.allocation)
+            .ok_or(GiveawayError::ExceedError)?;
+        require!(
+            allocated_amount.saturating_sub(giveaway_pool.claimed_amount)
+                <= giveaway_pool.remaining_amount,
+            GiveawayError::ExceedError
+        );
+        giveaway_pool.allocated_amount = allocated_amount;
+        giveaway_pool.claimed_count += 1;
+
+        vesting_account.beneficiary = *beneficiary;
+        vesting_account.allocation = args.allocation;
+        vesting_account.withdrawn = 0;
+    } else {
+        // 取消或过期后仍可领取已释放的部分，暂停期间不能领取
+        require!(
+            giveaway_pool.status != GiveawayStatus::Paused,
+            GiveawayError::Paused
+        );
+    }
+
+    let amount = giveaway_pool
+        .vested_amount(vesting_account.allocation, current_timestamp)?
+        .checked_sub(vesting_account.withdrawn)
+        .ok_or(GiveawayError::ExceedError)?;
+    // cliff 前可以只登记额度，登记后退款会保留这部分
+    require!(amount > 0 || registering, GiveawayError::NotVested);
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    giveaway_pool.remaining_amount = giveaway_pool
+        .remaining_amount
+        .checked_sub(amount)
+        .ok_or(GiveawayError::ExceedError)?;
+    giveaway_pool.claimed_amount = giveaway_pool
+        .claimed_amount
+        .checked_add(amount)
+        .ok_or(GiveawayError::ExceedError)?;
+    vesting_account.withdrawn += amount;
+    Ok(amount)
+}
+
+// 校验批量领取中的一条并记录领取，失败时不修改红包状态，便于跳过
+fn receive_batch_entry(
+    giveaway_pool: &mut GiveawayPool,
+    giveaway_id: &[u8; 20],
+    entry: &BatchReceiveEntry,
+    accounts: &[AccountInfo],
+    clock: &Clock,
+    instructions: &AccountInfo,
+) -> Result<u128> {
+    // [领取钱包, 领取凭证, 领取人凭证（仅凭证模式）]
+    let (wallet, claim_receipt, claimer_receipt) = (&accounts[0], &accounts[1], accounts.get(2));
+    require!(
+        entry.timestamp > clock.unix_timestamp.unsigned_abs(),
+        GiveawayError::Overtime
+    );
+    require!(
+        wallet.is_writable && wallet.key.to_bytes() == entry.wallet_address,
+        GiveawayError::ArgsError
+    );
+    let (receipt, _bump) = utils::claim_receipt_address(giveaway_id, entry.nonce);
+    require!(
+        claim_receipt.is_writable && claim_receipt.key() == receipt,
+        GiveawayError::ArgsError
+    );
+    require!(claim_receipt.owner != &crate::ID, GiveawayError::NonceUsed);
+    if let Some(claimer_receipt) = claimer_receipt {
+        let (receipt, _bump) = utils::claimer_receipt_address(giveaway_id, wallet.key);
+        require!(
+            claimer_receipt.is_writable && claimer_receipt.key() == receipt,
+            GiveawayError::ArgsError
+        );
+        require!(
+            claimer_receipt.owner != &crate::ID,
+            GiveawayError::AlreadyReceived
+        );
+    }
+
+    let amount = giveaway_pool.claim_amount(entry.amount, clock.slot)?;
+    require!(
+        amount <= giveaway_pool.remaining_amount,
+        GiveawayError::ExceedError
+    );
+
+    let claim = ClaimMessage {
+        wallet: entry.wallet_address,
+        giveaway_id: *giveaway_id,
+        timestamp: entry.timestamp,
+        nonce: entry.nonce,
+        index: entry.index,
+        amount: giveaway_pool.mode.signs_amount().then_some(amount),
+    };
+    signature::verify_claim(
+        &giveaway_pool.signers,
+        &giveaway_pool.signature_format,
+        &claim,
+        &entry.signature,
+        instructions,
+    )?;
+
+    giveaway_pool.record_receive(*wallet.key, amount, entry.index)?;
+    Ok(amount)
+}
+
+#[derive(Accounts)]
+#[instruction(args: CreateGiveawayARG)]
+pub struct CreateNonPutGiveawayAccounts<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    rent: Sysvar<'info, Rent>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    /// CHECK:
+    #[account(mut, token::mint = token_mint)]
+    from_account: Account<'info, TokenAccount>,
+    /// CHECK:
+    #[account(init, payer = payer, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
+    token_pool: Account<'info, TokenAccount>,
+    token_mint: Account<'info, Mint>,
+
+    #[account(init, payer = payer, space = GiveawayPool::space(args.giveaway_count, args.record_mode), seeds = [&args.giveaway_id.clone()], bump)]
     giveaway_pool: Account<'info, GiveawayPool>,
 }
 
@@ -433,37 +1554,659 @@ pub struct CreatePutGiveawayAccounts<'info> {
     system_program: Program<'info, System>,
     #[account(mut)]
     payer: Signer<'info>,
-    #[account(init_if_needed, payer = payer, space = 32 + 8 + 16 + 4 + usize::try_from(args.giveaway_count).unwrap() * 32, seeds = [&args.giveaway_id.clone()], bump)]
+    #[account(init, payer = payer, space = GiveawayPool::space(args.giveaway_count, args.record_mode), seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ReceivePutGiveawayARG)]
+pub struct ReceivePutGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
     giveaway_pool: Account<'info, GiveawayPool>,
+    /// CHECK: 领取凭证，在指令中手动创建以返回 NonceUsed
+    #[account(mut, seeds = [CLAIM_RECEIPT_SEED, &args.giveaway_id, &args.nonce.to_le_bytes()], bump)]
+    claim_receipt: UncheckedAccount<'info>,
+    /// CHECK: 指令 sysvar，用于校验 ed25519 签名
+    #[account(address = instructions_sysvar::ID)]
+    instructions: UncheckedAccount<'info>,
+    /// CHECK: 领取人凭证，凭证模式下在指令中创建以防止重复领取
+    #[account(mut, seeds = [CLAIMER_RECEIPT_SEED, &args.giveaway_id, &args.wallet_address], bump)]
+    claimer_receipt: UncheckedAccount<'info>,
 }
 
-#[derive(Accounts)]
-#[instruction(args: ReceivePutGiveawayARG)]
-pub struct ReceivePutGiveawayAccount<'info> {
-    system_program: Program<'info, System>,
-    #[account(mut)]
-    payer: Signer<'info>,
-    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
-    giveaway_pool: Account<'info, GiveawayPool>,
-}
+// 线性释放红包的受益人账户
+#[account]
+pub struct VestingAccount {
+    beneficiary: Pubkey,
+    allocation: u128, // 分配额度
+    withdrawn: u128,  // 已领取金额
+}
+
+impl VestingAccount {
+    pub const SPACE: usize = 8 + 32 + 16 + 16;
+}
+
+// PDA 账户
+#[account]
+pub struct GiveawayPool {
+    creator: Pubkey,
+    receive_records: Vec<Pubkey>, // 领取记录，记录领取的钱包，凭证模式下为空
+    total_amount: u128,           // 红包总金额
+    giveaway_count: u32,          // 红包个数，即最多可领取的钱包数
+    remaining_amount: u128,       // 剩余金额
+    claimed_amount: u128,         // 已领取金额
+    claimed_count: u32,           // 已领取次数
+    allocated_amount: u128,       // 线性释放红包已登记的分配额度总和
+    token_mint: Pubkey,           // 红包币种，主币红包为默认值
+    expire_time: Option<u64>,     // 过期时间，过期后不能领取，未取消的红包过期后才能退款
+    start_time: Option<u64>,      // 开始时间，开始前不能领取
+    status: GiveawayStatus,
+    mode: GiveawayMode,
+    signers: Vec<ClaimSigner>, // 领取签名方，任意一个签名即可领取
+    signature_format: SignatureFormat,
+    record_mode: RecordMode,
+    claimed_bitmap: Vec<u8>,  // 位图红包的领取位图，第 i 位表示序号 i 已领取
+    bundle: Vec<BundleAsset>, // 组合红包的代币，每次加入时账户扩容
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BundleAsset {
+    token_mint: Pubkey,
+    amount: u128,           // 每份数量
+    remaining_amount: u128, // 剩余数量
+}
+
+impl BundleAsset {
+    pub const SIZE: usize = 32 + 16 + 16;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RecordMode {
+    // 领取钱包记录在红包账户中，红包个数受账户大小限制
+    List,
+    // 每次领取创建 (红包ID, 钱包) 领取人凭证 PDA，红包账户只记录计数
+    Receipt,
+    // 签名方或白名单叶子为每个领取分配序号，领取时置位图中对应的位
+    Bitmap,
+}
+
+impl RecordMode {
+    // 批量领取时每条记录在 remaining_accounts 中占用的账户数
+    pub fn batch_accounts(&self) -> usize {
+        match self {
+            RecordMode::List | RecordMode::Bitmap => 2,
+            RecordMode::Receipt => 3,
+        }
+    }
+
+    // 领取位图的字节数，每个序号占 1 位
+    pub fn bitmap_len(&self, giveaway_count: u32) -> usize {
+        match self {
+            RecordMode::Bitmap => (giveaway_count as usize).div_ceil(8),
+            RecordMode::List | RecordMode::Receipt => 0,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimSigner {
+    // 以太坊密钥，签名为 keccak256 后的 secp256k1 签名
+    Secp256k1 { address: [u8; 20] },
+    // 原生 ed25519 密钥，签名通过交易中的 ed25519 预编译指令校验
+    Ed25519 { pubkey: Pubkey },
+}
+
+impl ClaimSigner {
+    pub const SIZE: usize = 1 + 32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureFormat {
+    // keccak256(消息)
+    Raw,
+    // personal_sign：keccak256("\x19Ethereum Signed Message:\n" + 消息长度 + 消息)
+    Eip191,
+    // signTypedData：域绑定本程序ID和链ID
+    Eip712 { chain_id: u64 },
+}
+
+impl SignatureFormat {
+    pub const SIZE: usize = 1 + 8;
+
+    // 检查签名方数量，以太坊消息格式只适用于 secp256k1 签名方
+    pub fn validate(&self, signers: &[ClaimSigner], mode: &GiveawayMode) -> Result<()> {
+        // 白名单红包凭证明领取，可以不设置签名方
+        require!(
+            !signers.is_empty() || matches!(mode, GiveawayMode::Merkle { .. }),
+            GiveawayError::ArgsError
+        );
+        require!(signers.len() <= MAX_CLAIM_SIGNERS, GiveawayError::ArgsError);
+        for signer in signers {
+            if let ClaimSigner::Ed25519 { .. } = signer {
+                require!(*self == SignatureFormat::Raw, GiveawayError::ArgsError);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GiveawayStatus {
+    Active,
+    Cancelled, // 取消后不能再领取，可以立即退款
+    Paused,    // 暂停期间不能领取，创建者可以恢复
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GiveawayMode {
+    // 普通红包，每次领取金额由签名指定
+    Signed,
+    // 拼手气红包，由程序根据种子、slot 和领取序号随机计算金额，最后一人领取剩余全部
+    Lucky {
+        seed: [u8; 32],
+        min_amount: u128,
+        max_amount: u128,
+    },
+    // 均分红包，每人领取 amount / giveaway_count
+    Equal,
+    // 白名单红包，凭 (钱包, 金额) 叶子的 Merkle 证明领取
+    Merkle {
+        root: [u8; 32],
+    },
+    // 线性释放红包，每个受益人的额度在 cliff_time 前不释放，之后按 start_time 到 end_time 线性释放
+    // 额度由签名方签名，或由 (钱包, 额度) 叶子的 Merkle 证明登记，root 为 0 时只接受签名
+    Vesting {
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+        root: [u8; 32],
+    },
+    // 组合红包，每份包含 amount 主币和每种已加入代币各一份，总金额必须为 amount * giveaway_count
+    Bundle {
+        amount: u128,
+    },
+}
+
+impl GiveawayMode {
+    pub const SIZE: usize = 1 + 32 + 16 + 16;
+
+    // 签名是否包含领取金额
+    pub fn signs_amount(&self) -> bool {
+        matches!(self, GiveawayMode::Signed)
+    }
+
+    pub fn is_vesting(&self) -> bool {
+        matches!(self, GiveawayMode::Vesting { .. })
+    }
+
+    pub fn is_bundle(&self) -> bool {
+        matches!(self, GiveawayMode::Bundle { .. })
+    }
+
+    // 创建时检查模式参数与红包金额、个数是否匹配
+    pub fn validate(&self, amount: u128, giveaway_count: u32) -> Result<()> {
+        let count = giveaway_count as u128;
+        match *self {
+            GiveawayMode::Signed => {}
+            GiveawayMode::Lucky {
+                min_amount,
+                max_amount,
+                ..
+            } => {
+                require!(min_amount <= max_amount, GiveawayError::ArgsError);
+                require!(
+                    min_amount
+                        .checked_mul(count)
+                        .is_some_and(|min_total| min_total <= amount),
+                    GiveawayError::ArgsError
+                );
+                require!(
+                    max_amount
+                        .checked_mul(count)
+                        .is_none_or(|max_total| amount <= max_total),
+                    GiveawayError::ArgsError
+                );
+            }
+            // 每人至少能领到 1
+            GiveawayMode::Equal => require!(amount >= count, GiveawayError::ArgsError),
+            GiveawayMode::Merkle { root } => require!(root != [0u8; 32], GiveawayError::ArgsError),
+            GiveawayMode::Vesting {
+                start_time,
+                cliff_time,
+                end_time,
+                ..
+            } => require!(
+                start_time <= cliff_time && cliff_time <= end_time && start_time < end_time,
+                GiveawayError::ArgsError
+            ),
+            // 每份数量固定，因此不能追加金额或增加个数
+            GiveawayMode::Bundle { amount: per_claim } => require!(
+                per_claim.checked_mul(count) == Some(amount),
+                GiveawayError::ArgsError
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl GiveawayPool {
+    pub fn space(giveaway_count: u32, record_mode: RecordMode) -> usize {
+        let records = match record_mode {
+            RecordMode::List => giveaway_count as usize,
+            RecordMode::Receipt | RecordMode::Bitmap => 0,
+        };
+        8 // discriminator
+            + 32 // creator
+            + 4 + records * 32 // receive_records
+            + 16 // total_amount
+            + 4 // giveaway_count
+            + 16 // remaining_amount
+            + 16 // claimed_amount
+            + 4 // claimed_count
+            + 16 // allocated_amount
+            + 32 // token_mint
+            + 1 + 8 // expire_time
+            + 1 + 8 // start_time
+            + 1 // status
+            + GiveawayMode::SIZE // mode
+            + 4 + MAX_CLAIM_SIGNERS * ClaimSigner::SIZE // signers
+            + SignatureFormat::SIZE // signature_format
+            + 1 // record_mode
+            + 4 + record_mode.bitmap_len(giveaway_count) // claimed_bitmap
+            + 4 // bundle
+    }
+
+    // 校验创建参数并初始化红包账户，主币红包的 token_mint 为默认值
+    pub fn init(
+        &mut self,
+        args: &CreateGiveawayARG,
+        creator: Pubkey,
+        token_mint: Pubkey,
+        current_timestamp: u64,
+    ) -> Result<()> {
+        require!(args.giveaway_count > 0, GiveawayError::ArgsError);
+        // 组合红包以主币红包创建，代币通过 add_bundle_asset 加入
+        require!(
+            token_mint == Pubkey::default() || !args.mode.is_bundle(),
+            GiveawayError::ModeMismatch
+        );
+        args.mode.validate(args.amount, args.giveaway_count)?;
+        args.signature_format.validate(&args.signers, &args.mode)?;
+        // 线性释放红包由受益人账户记录领取，红包账户只记录计数
+        require!(
+            !args.mode.is_vesting() || args.record_mode == RecordMode::Receipt,
+            GiveawayError::ArgsError
+        );
+        if let Some(expire_time) = args.expire_time {
+            require!(expire_time > current_timestamp, GiveawayError::ArgsError);
+            // 开始时间必须早于过期时间
+            if let Some(start_time) = args.start_time {
+                require!(start_time < expire_time, GiveawayError::ArgsError);
+            }
+        }
+
+        self.creator = creator;
+        self.receive_records = Vec::new();
+        self.total_amount = args.amount;
+        self.remaining_amount = args.amount;
+        self.claimed_amount = 0;
+        self.claimed_count = 0;
+        self.allocated_amount = 0;
+        self.giveaway_count = args.giveaway_count;
+        self.expire_time = args.expire_time;
+        self.start_time = args.start_time;
+        self.status = GiveawayStatus::Active;
+        self.mode = args.mode;
+        self.signers = args.signers.clone();
+        self.signature_format = args.signature_format;
+        self.record_mode = args.record_mode;
+        self.claimed_bitmap = vec![0; args.record_mode.bitmap_len(args.giveaway_count)];
+        self.bundle = Vec::new();
+        self.token_mint = token_mint;
+        Ok(())
+    }
+
+    // 计算本次领取金额，普通红包使用签名中的金额，其他模式由程序计算，最后一人领取剩余全部
+    pub fn claim_amount(&self, signed_amount: u128, slot: u64) -> Result<u128> {
+        if self.mode.signs_amount() {
+            return Ok(signed_amount);
+        }
+        // 白名单红包只能凭证明领取，线性释放红包只能通过 claim_vested 领取
+        require!(
+            !matches!(
+                self.mode,
+                GiveawayMode::Merkle { .. }
+                    | GiveawayMode::Vesting { .. }
+                    | GiveawayMode::Bundle { .. }
+            ),
+            GiveawayError::ModeMismatch
+        );
+
+        require!(signed_amount == 0, GiveawayError::ArgsError);
+        // 退款后余额为 0，均分红包不能再领取空红包
+        require!(
+            self.mode != GiveawayMode::Equal || self.remaining_amount > 0,
+            GiveawayError::ExceedError
+        );
+        let left_count = self
+            .giveaway_count
+            .checked_sub(self.claimed_count)
+            .filter(|count| *count > 0)
+            .ok_or(GiveawayError::AllReceived)?;
+        if left_count == 1 {
+            return Ok(self.remaining_amount);
+        }
+
+        match self.mode {
+            GiveawayMode::Signed => Ok(signed_amount),
+            GiveawayMode::Lucky {
+                seed,
+                min_amount,
+                max_amount,
+            } => {
+                // 保证剩余的人都能领到 [min_amount, max_amount] 之间的金额
+                let others = (left_count - 1) as u128;
+                let low = self
+                    .remaining_amount
+                    .saturating_sub(max_amount.saturating_mul(others))
+                    .max(min_amount);
+                let high = self
+                    .remaining_amount
+                    .saturating_sub(min_amount.saturating_mul(others))
+                    .min(max_amount);
+                if high <= low {
+                    return Ok(low.min(self.remaining_amount));
+                }
+                Ok(low + utils::lucky_draw(&seed, slot, self.claimed_count, high - low))
+            }
+            GiveawayMode::Equal => Ok(self.equal_amount().min(self.remaining_amount)),
+            GiveawayMode::Merkle { .. }
+            | GiveawayMode::Vesting { .. }
+            | GiveawayMode::Bundle { .. } => err!(GiveawayError::ModeMismatch),
+        }
+    }
+
+    // 校验白名单红包的 Merkle 证明
+    pub fn verify_proof(
+        &self,
+        wallet: &Pubkey,
+        amount: u128,
+        index: Option<u32>,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        let root = match self.mode {
+            GiveawayMode::Merkle { root } => root,
+            _ => return err!(GiveawayError::ModeMismatch),
+        };
+        let leaf = utils::merkle_leaf(wallet, amount, index);
+        require!(
+            utils::verify_merkle_proof(proof, root, leaf),
+            GiveawayError::InvalidProof
+        );
+        Ok(())
+    }
+
+    // 校验线性释放红包受益人的分配额度
+    pub fn verify_allocation(
+        &self,
+        giveaway_id: &[u8; 20],
+        beneficiary: &Pubkey,
+        allocation: u128,
+        proof: &AllocationProof,
+        current_timestamp: u64,
+        instructions: &AccountInfo,
+    ) -> Result<()> {
+        let root = match self.mode {
+            GiveawayMode::Vesting { root, .. } => root,
+            _ => return err!(GiveawayError::ModeMismatch),
+        };
+        match proof {
+            AllocationProof::Merkle { proof } => {
+                require!(root != [0u8; 32], GiveawayError::InvalidProof);
+                let leaf = utils::merkle_leaf(beneficiary, allocation, None);
+                require!(
+                    utils::verify_merkle_proof(proof, root, leaf),
+                    GiveawayError::InvalidProof
+                );
+            }
+            // 受益人账户只登记一次，签名不需要 nonce，固定为 0
+            AllocationProof::Signature {
+                timestamp,
+                signature,
+            } => {
+                require!(*timestamp > current_timestamp, GiveawayError::Overtime);
+                let claim = ClaimMessage {
+                    wallet: beneficiary.to_bytes(),
+                    giveaway_id: *giveaway_id,
+                    timestamp: *timestamp,
+                    nonce: 0,
+                    index: None,
+                    amount: Some(allocation),
+                };
+                signature::verify_claim(
+                    &self.signers,
+                    &self.signature_format,
+                    &claim,
+                    signature,
+                    instructions,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // 截至当前时间已释放的额度
+    pub fn vested_amount(&self, allocation: u128, current_timestamp: u64) -> Result<u128> {
+        let (start_time, cliff_time, end_time) = match self.mode {
+            GiveawayMode::Vesting {
+                start_time,
+                cliff_time,
+                end_time,
+                ..
+            } => (start_time, cliff_time, end_time),
+            _ => return err!(GiveawayError::ModeMismatch),
+        };
+        // 过期（含取消）后停止释放
+        let current_timestamp = self.expire_time.map_or(current_timestamp, |expire_time| {
+            current_timestamp.min(expire_time)
+        });
+        if current_timestamp < cliff_time {
+            return Ok(0);
+        }
+        if current_timestamp >= end_time {
+            return Ok(allocation);
+        }
+        let elapsed = (current_timestamp - start_time) as u128;
+        let duration = (end_time - start_time) as u128;
+        Ok(allocation
+            .checked_mul(elapsed)
+            .ok_or(GiveawayError::ExceedError)?
+            / duration)
+    }
+
+    // 均分红包每人金额，除不尽的部分由最后一人领取
+    pub fn equal_amount(&self) -> u128 {
+        self.total_amount / self.giveaway_count as u128
+    }
+
+    // 检查红包当前是否可以领取
+    pub fn check_claimable(&self, current_timestamp: u64) -> Result<()> {
+        require!(self.status != GiveawayStatus::Paused, GiveawayError::Paused);
+        if let Some(start_time) = self.start_time {
+            require!(current_timestamp >= start_time, GiveawayError::NotStarted);
+        }
+        self.check_open(current_timestamp)
+    }
+
+    // 检查红包未取消且未过期
+    pub fn check_open(&self, current_timestamp: u64) -> Result<()> {
+        require!(
+            self.status != GiveawayStatus::Cancelled,
+            GiveawayError::Cancelled
+        );
+        if let Some(expire_time) = self.expire_time {
+            require!(current_timestamp < expire_time, GiveawayError::Expired);
+        }
+        Ok(())
+    }
+
+    // 检查红包当前是否可以退款
+    pub fn check_refundable(&self, current_timestamp: u64) -> Result<()> {
+        if self.status == GiveawayStatus::Cancelled {
+            return Ok(());
+        }
+        if let Some(expire_time) = self.expire_time {
+            require!(current_timestamp >= expire_time, GiveawayError::NotExpired);
+        }
+        Ok(())
+    }
+
+    // 记录领取钱包并扣减余额，同一钱包不能重复领取，领取人数不能超过红包个数
+    pub fn record_receive(
+        &mut self,
+        wallet: Pubkey,
+        amount: u128,
+        index: Option<u32>,
+    ) -> Result<()> {
+        // 位图红包必须指定领取序号，其他红包不能指定
+        require!(
+            index.is_some() == (self.record_mode == RecordMode::Bitmap),
+            GiveawayError::ArgsError
+        );
+        // 凭证模式下由领取人凭证账户防止重复领取
+        let list = self.record_mode == RecordMode::List;
+        require!(
+            !(list && self.receive_records.contains(&wallet)),
+            GiveawayError::AlreadyReceived
+        );
+        if let Some(index) = index {
+            require!(index < self.giveaway_count, GiveawayError::ArgsError);
+            require!(
+                self.claimed_bitmap[index as usize / 8] & (1 << (index % 8)) == 0,
+                GiveawayError::AlreadyReceived
+            );
+        }
+        require!(
+            self.claimed_count < self.giveaway_count,
+            GiveawayError::AllReceived
+        );
+        self.remaining_amount = self
+            .remaining_amount
+            .checked_sub(amount)
+            .ok_or(GiveawayError::ExceedError)?;
+        self.claimed_amount = self
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(GiveawayError::ExceedError)?;
+        self.claimed_count += 1;
+        if list {
+            self.receive_records.push(wallet);
+        }
+        if let Some(index) = index {
+            self.claimed_bitmap[index as usize / 8] |= 1 << (index % 8);
+        }
+        Ok(())
+    }
+
+    // 追加金额，已取消或已过期的红包不能追加，暂停中的可以
+    pub fn top_up(&mut self, amount: u128, current_timestamp: u64) -> Result<()> {
+        require!(amount > 0, GiveawayError::ArgsError);
+        self.check_open(current_timestamp)?;
+        // 均分红包每份金额按总金额计算，已有人领取后追加会让前后领取的金额不一致
+        require!(
+            self.claimed_count == 0 || self.mode != GiveawayMode::Equal,
+            GiveawayError::ArgsError
+        );
+        self.total_amount = self
+            .total_amount
+            .checked_add(amount)
+            .ok_or(GiveawayError::ExceedError)?;
+        self.remaining_amount = self
+            .remaining_amount
+            .checked_add(amount)
+            .ok_or(GiveawayError::ExceedError)?;
+        self.mode.validate(self.total_amount, self.giveaway_count)
+    }
+
+    // 组合红包每份的主币数量
+    pub fn bundle_amount(&self) -> Result<u128> {
+        match self.mode {
+            GiveawayMode::Bundle { amount } => Ok(amount),
+            _ => err!(GiveawayError::ModeMismatch),
+        }
+    }
+
+    // 加入一种代币，返回需要转入代币池的总数量
+    pub fn add_bundle_asset(
+        &mut self,
+        token_mint: Pubkey,
+        amount: u128,
+        current_timestamp: u64,
+    ) -> Result<u128> {
+        self.bundle_amount()?;
+        self.check_open(current_timestamp)?;
+        // 已有人领取后再加入会导致每份内容不一致
+        require!(self.claimed_count == 0, GiveawayError::ArgsError);
+        require!(amount > 0, GiveawayError::ArgsError);
+        require!(
+            self.bundle.len() < MAX_BUNDLE_ASSETS,
+            GiveawayError::ArgsError
+        );
+        require!(
+            self.bundle
+                .iter()
+                .all(|asset| asset.token_mint != token_mint),
+            GiveawayError::ArgsError
+        );
+        let total_amount = amount
+            .checked_mul(self.giveaway_count as u128)
+            .ok_or(GiveawayError::ExceedError)?;
+        self.bundle.push(BundleAsset {
+            token_mint,
+            amount,
+            remaining_amount: total_amount,
+        });
+        Ok(total_amount)
+    }
+
+    // 线性释放红包退款时需要保留的金额：停止释放前保留全部已登记额度，之后保留已释放未领取的部分
+    pub fn reserved_amount(&self, current_timestamp: u64) -> Result<u128> {
+        if !self.mode.is_vesting() {
+            return Ok(0);
+        }
+        let stopped =
+            matches!(self.expire_time, Some(expire_time) if current_timestamp >= expire_time);
+        let owed = if stopped {
+            self.vested_amount(self.allocated_amount, current_timestamp)?
+        } else {
+            self.allocated_amount
+        };
+        Ok(owed.saturating_sub(self.claimed_amount))
+    }
 
-// PDA 账户
-#[account]
-pub struct GiveawayPool {
-    creator: Pubkey,
-    receive_records: Vec<Pubkey>, // 领取记录，记录领取的钱包
-    total_amount: u128,           // 红包总金额
+    // 退款时取出剩余金额，线性释放红包保留受益人应得的部分
+    pub fn take_remaining(&mut self, current_timestamp: u64) -> Result<u128> {
+        let reserved = self.reserved_amount(current_timestamp)?;
+        let remain = self.remaining_amount.saturating_sub(reserved);
+        self.remaining_amount -= remain;
+        Ok(remain)
+    }
 }
 
-// impl GiveawayPool {
-//     pub const MAX_SIZE: usize = 32 + 4 + 16;
-// }
-
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct CreateGiveawayARG {
     giveaway_id: [u8; 20], // 红包ID
     giveaway_count: u32,
-    amount: u128, // 红包总金额
+    amount: u128,             // 红包总金额
+    expire_time: Option<u64>, // 过期时间（秒），None 表示不过期
+    start_time: Option<u64>,  // 开始时间（秒），None 表示创建后即可领取
+    mode: GiveawayMode,
+    signers: Vec<ClaimSigner>, // 领取签名方，最多 MAX_CLAIM_SIGNERS 个
+    signature_format: SignatureFormat,
+    record_mode: RecordMode,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -472,14 +2215,19 @@ pub struct ReceivePutGiveawayARG {
     wallet_address: [u8; 32], // 领取钱包地址
     amount: u128,
     timestamp: u64,
+    nonce: u64,         // 领取凭证号，每个签名唯一，防止重放
+    index: Option<u32>, // 领取序号，仅位图红包使用
     signature: [u8; 65],
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ReceiveNonPutGiveawayARG {
-    giveaway_id: [u8; 20], // 红包ID
+    giveaway_id: [u8; 20],    // 红包ID
+    wallet_address: [u8; 32], // 领取钱包地址，即收款代币账户的所有者
     amount: u128,
     timestamp: u64,
+    nonce: u64,         // 领取凭证号，每个签名唯一，防止重放
+    index: Option<u32>, // 领取序号，仅位图红包使用
     signature: [u8; 65],
 }
 
@@ -493,11 +2241,154 @@ pub struct ReceiveNonPutGiveawayAccount<'info> {
     #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
     giveaway_pool: Account<'info, GiveawayPool>,
     /// CHECK:
-    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [&payer.key().to_bytes(), &token_mint.key().to_bytes()], bump)]
+    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
+    token_pool: Account<'info, TokenAccount>,
+    // 签名钱包的关联代币账户，不存在时由 payer 支付租金创建，payer 可以是代付手续费的中继
+    #[account(init_if_needed, payer = payer, associated_token::mint = token_mint, associated_token::authority = wallet)]
+    to_account: Account<'info, TokenAccount>,
+    token_mint: Account<'info, Mint>,
+    /// CHECK: 签名中的领取钱包，只用于推导关联代币账户
+    #[account(address = Pubkey::new_from_array(args.wallet_address))]
+    wallet: UncheckedAccount<'info>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
+    /// CHECK: 领取凭证，在指令中手动创建以返回 NonceUsed
+    #[account(mut, seeds = [CLAIM_RECEIPT_SEED, &args.giveaway_id, &args.nonce.to_le_bytes()], bump)]
+    claim_receipt: UncheckedAccount<'info>,
+    /// CHECK: 指令 sysvar，用于校验 ed25519 签名
+    #[account(address = instructions_sysvar::ID)]
+    instructions: UncheckedAccount<'info>,
+    /// CHECK: 领取人凭证，凭证模式下在指令中创建以防止重复领取
+    #[account(mut, seeds = [CLAIMER_RECEIPT_SEED, &args.giveaway_id, &args.wallet_address], bump)]
+    claimer_receipt: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchReceiveEntry {
+    wallet_address: [u8; 32], // 领取钱包地址
+    amount: u128,
+    timestamp: u64,
+    nonce: u64,
+    index: Option<u32>,
+    signature: [u8; 65],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchReceivePutGiveawayARG {
+    giveaway_id: [u8; 20], // 红包ID
+    entries: Vec<BatchReceiveEntry>,
+    skip_invalid: bool, // 是否跳过校验失败的领取
+}
+
+// 由中继提交，payer 支付领取凭证的租金，领取钱包通过 remaining_accounts 传入
+#[derive(Accounts)]
+#[instruction(args: BatchReceivePutGiveawayARG)]
+pub struct BatchReceivePutGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    /// CHECK: 指令 sysvar，用于校验 ed25519 签名
+    #[account(address = instructions_sysvar::ID)]
+    instructions: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum AllocationProof {
+    // 签名方对 (钱包, 红包ID, 截止时间, 额度) 的签名
+    Signature { timestamp: u64, signature: [u8; 65] },
+    // (钱包, 额度) 叶子到根的 Merkle 路径
+    Merkle { proof: Vec<[u8; 32]> },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimVestedARG {
+    giveaway_id: [u8; 20],          // 红包ID
+    allocation: u128,               // 分配额度，只在首次领取时校验
+    proof: Option<AllocationProof>, // 首次领取时必填
+}
+
+#[derive(Accounts)]
+#[instruction(args: ClaimVestedARG)]
+pub struct ClaimVestedPutAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    #[account(init_if_needed, payer = payer, space = VestingAccount::SPACE, seeds = [VESTING_SEED, &args.giveaway_id, &payer.key().to_bytes()], bump)]
+    vesting_account: Account<'info, VestingAccount>,
+    /// CHECK: 指令 sysvar，用于校验 ed25519 签名
+    #[account(address = instructions_sysvar::ID)]
+    instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ClaimVestedARG)]
+pub struct ClaimVestedTokenAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    #[account(init_if_needed, payer = payer, space = VestingAccount::SPACE, seeds = [VESTING_SEED, &args.giveaway_id, &payer.key().to_bytes()], bump)]
+    vesting_account: Account<'info, VestingAccount>,
+    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
     token_pool: Account<'info, TokenAccount>,
+    #[account(init_if_needed, payer = payer, associated_token::mint = token_mint, associated_token::authority = payer)]
+    to_account: Account<'info, TokenAccount>,
+    token_mint: Account<'info, Mint>,
+    /// CHECK: 指令 sysvar，用于校验 ed25519 签名
+    #[account(address = instructions_sysvar::ID)]
+    instructions: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReceiveProofARG {
+    giveaway_id: [u8; 20], // 红包ID
+    amount: u128,          // 叶子中的领取金额
+    index: Option<u32>,    // 叶子中的领取序号，仅位图红包使用
+    proof: Vec<[u8; 32]>,  // 叶子到根的 Merkle 路径
+}
+
+#[derive(Accounts)]
+#[instruction(args: ReceiveProofARG)]
+pub struct ReceivePutProofAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    /// CHECK: 领取人凭证，凭证模式下在指令中创建以防止重复领取
+    #[account(mut, seeds = [CLAIMER_RECEIPT_SEED, &args.giveaway_id, &payer.key().to_bytes()], bump)]
+    claimer_receipt: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ReceiveProofARG)]
+pub struct ReceiveNonPutProofAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
     #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    /// CHECK:
+    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
+    token_pool: Account<'info, TokenAccount>,
+    // 领取人的关联代币账户，首次领取时创建
+    #[account(init_if_needed, payer = payer, associated_token::mint = token_mint, associated_token::authority = payer)]
     to_account: Account<'info, TokenAccount>,
     token_mint: Account<'info, Mint>,
+    /// CHECK: 领取人凭证，凭证模式下在指令中创建以防止重复领取
+    #[account(mut, seeds = [CLAIMER_RECEIPT_SEED, &args.giveaway_id, &payer.key().to_bytes()], bump)]
+    claimer_receipt: UncheckedAccount<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
@@ -505,6 +2396,102 @@ pub struct RefundPutGiveawayARGS {
     giveaway_id: [u8; 20], // 红包ID
 }
 
+#[derive(Accounts)]
+#[instruction(args: RefundPutGiveawayARGS)]
+pub struct RefundBundleGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct AddBundleAssetARGS {
+    giveaway_id: [u8; 20], // 红包ID
+    amount: u128,          // 每份数量
+}
+
+#[derive(Accounts)]
+#[instruction(args: AddBundleAssetARGS)]
+pub struct AddBundleAssetAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    rent: Sysvar<'info, Rent>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump, realloc = giveaway_pool.to_account_info().data_len() + BundleAsset::SIZE, realloc::payer = payer, realloc::zero = false)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    #[account(mut, token::mint = token_mint)]
+    from_account: Account<'info, TokenAccount>,
+    #[account(init, payer = payer, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
+    token_pool: Account<'info, TokenAccount>,
+    token_mint: Account<'info, Mint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReceiveBundleGiveawayARG {
+    giveaway_id: [u8; 20],    // 红包ID
+    wallet_address: [u8; 32], // 领取钱包地址
+    timestamp: u64,
+    nonce: u64,         // 领取凭证号，每个签名唯一，防止重放
+    index: Option<u32>, // 领取序号，仅位图红包使用
+    signature: [u8; 65],
+}
+
+#[derive(Accounts)]
+#[instruction(args: ReceiveBundleGiveawayARG)]
+pub struct ReceiveBundleGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    /// CHECK: 领取凭证，在指令中手动创建以返回 NonceUsed
+    #[account(mut, seeds = [CLAIM_RECEIPT_SEED, &args.giveaway_id, &args.nonce.to_le_bytes()], bump)]
+    claim_receipt: UncheckedAccount<'info>,
+    /// CHECK: 指令 sysvar，用于校验 ed25519 签名
+    #[account(address = instructions_sysvar::ID)]
+    instructions: UncheckedAccount<'info>,
+    /// CHECK: 领取人凭证，凭证模式下在指令中创建以防止重复领取
+    #[account(mut, seeds = [CLAIMER_RECEIPT_SEED, &args.giveaway_id, &args.wallet_address], bump)]
+    claimer_receipt: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct TopUpGiveawayARGS {
+    giveaway_id: [u8; 20], // 红包ID
+    amount: u128,          // 追加金额
+}
+
+#[derive(Accounts)]
+#[instruction(args: TopUpGiveawayARGS)]
+pub struct TopUpPutGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: TopUpGiveawayARGS)]
+pub struct TopUpTokenGiveawayAccount<'info> {
+    token_program: Program<'info, Token>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    #[account(mut, token::mint = token_mint)]
+    from_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
+    token_pool: Account<'info, TokenAccount>,
+    token_mint: Account<'info, Mint>,
+}
+
 #[derive(Accounts)]
 #[instruction(args: RefundPutGiveawayARGS)]
 pub struct RefundPutGiveawayAccount<'info> {
@@ -530,9 +2517,216 @@ pub struct RefundTokenGiveawayAccount<'info> {
     #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
     giveaway_pool: Account<'info, GiveawayPool>,
     /// CHECK:
-    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [&payer.key().to_bytes(), &token_mint.key().to_bytes()], bump)]
+    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
     token_pool: Account<'info, TokenAccount>,
     #[account(mut)]
     to_account: Account<'info, TokenAccount>,
     token_mint: Account<'info, Mint>,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct CloseGiveawayARGS {
+    giveaway_id: [u8; 20], // 红包ID
+}
+
+#[derive(Accounts)]
+#[instruction(args: CloseGiveawayARGS)]
+pub struct ClosePutGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, close = payer, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: CloseGiveawayARGS)]
+pub struct CloseBundleGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, close = payer, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: CloseGiveawayARGS)]
+pub struct CloseTokenGiveawayAccount<'info> {
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, close = payer, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+    /// CHECK:
+    #[account(mut, token::mint = token_mint, token::authority = token_pool, seeds = [TOKEN_POOL_SEED, &args.giveaway_id, &token_mint.key().to_bytes()], bump)]
+    token_pool: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = token_mint)]
+    to_account: Account<'info, TokenAccount>,
+    token_mint: Account<'info, Mint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct CancelGiveawayARGS {
+    giveaway_id: [u8; 20], // 红包ID
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct SetGiveawayCountARGS {
+    giveaway_id: [u8; 20], // 红包ID
+    giveaway_count: u32,   // 新的红包个数，只能增加
+}
+
+#[derive(Accounts)]
+#[instruction(args: SetGiveawayCountARGS)]
+pub struct SetGiveawayCountAccount<'info> {
+    system_program: Program<'info, System>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump, realloc = GiveawayPool::space(args.giveaway_count, giveaway_pool.record_mode), realloc::payer = payer, realloc::zero = false)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetSignersARGS {
+    giveaway_id: [u8; 20], // 红包ID
+    signers: Vec<ClaimSigner>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: SetSignersARGS)]
+pub struct SetSignersAccount<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct PauseGiveawayARGS {
+    giveaway_id: [u8; 20], // 红包ID
+}
+
+#[derive(Accounts)]
+#[instruction(args: PauseGiveawayARGS)]
+pub struct PauseGiveawayAccount<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: CancelGiveawayARGS)]
+pub struct CancelGiveawayAccount<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut, seeds = [&args.giveaway_id.clone()], bump)]
+    giveaway_pool: Account<'info, GiveawayPool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(mode: GiveawayMode, total_amount: u128, giveaway_count: u32) -> GiveawayPool {
+        GiveawayPool {
+            creator: Pubkey::new_unique(),
+            receive_records: Vec::new(),
+            total_amount,
+            giveaway_count,
+            remaining_amount: total_amount,
+            claimed_amount: 0,
+            claimed_count: 0,
+            allocated_amount: 0,
+            token_mint: Pubkey::default(),
+            expire_time: None,
+            start_time: None,
+            status: GiveawayStatus::Active,
+            mode,
+            signers: Vec::new(),
+            signature_format: SignatureFormat::Raw,
+            record_mode: RecordMode::Receipt,
+            claimed_bitmap: Vec::new(),
+            bundle: Vec::new(),
+        }
+    }
+
+    fn vesting_pool() -> GiveawayPool {
+        let mode = GiveawayMode::Vesting {
+            start_time: 100,
+            cliff_time: 150,
+            end_time: 200,
+            root: [0u8; 32],
+        };
+        pool(mode, 1000, 10)
+    }
+
+    #[test]
+    fn lucky_claim_amount_stays_within_bounds() {
+        let (min_amount, max_amount) = (50, 200);
+        let mode = GiveawayMode::Lucky {
+            seed: [7u8; 32],
+            min_amount,
+            max_amount,
+        };
+        for slot in 0..50 {
+            let mut pool = pool(mode, 1000, 10);
+            while pool.claimed_count < pool.giveaway_count {
+                let amount = pool.claim_amount(0, slot).unwrap();
+                assert!((min_amount..=max_amount).contains(&amount));
+                pool.remaining_amount -= amount;
+                pool.claimed_count += 1;
+            }
+            assert_eq!(pool.remaining_amount, 0);
+            assert_eq!(
+                pool.claim_amount(0, slot).unwrap_err(),
+                GiveawayError::AllReceived.into()
+            );
+        }
+    }
+
+    #[test]
+    fn lucky_claim_amount_rejects_signed_amount() {
+        let mode = GiveawayMode::Lucky {
+            seed: [7u8; 32],
+            min_amount: 1,
+            max_amount: 10,
+        };
+        assert_eq!(
+            pool(mode, 10, 2).claim_amount(5, 0).unwrap_err(),
+            GiveawayError::ArgsError.into()
+        );
+    }
+
+    #[test]
+    fn vested_amount_follows_schedule() {
+        let pool = vesting_pool();
+        assert_eq!(pool.vested_amount(1000, 0).unwrap(), 0);
+        assert_eq!(pool.vested_amount(1000, 149).unwrap(), 0);
+        // cliff 时释放从 start_time 起累计的部分
+        assert_eq!(pool.vested_amount(1000, 150).unwrap(), 500);
+        assert_eq!(pool.vested_amount(1000, 175).unwrap(), 750);
+        assert_eq!(pool.vested_amount(1000, 200).unwrap(), 1000);
+        assert_eq!(pool.vested_amount(1000, 300).unwrap(), 1000);
+    }
+
+    #[test]
+    fn vested_amount_stops_at_expire_time() {
+        let mut pool = vesting_pool();
+        pool.expire_time = Some(160);
+        assert_eq!(pool.vested_amount(1000, 155).unwrap(), 550);
+        assert_eq!(pool.vested_amount(1000, 300).unwrap(), 600);
+    }
+
+    #[test]
+    fn vested_amount_rejects_other_modes() {
+        assert_eq!(
+            pool(GiveawayMode::Equal, 1000, 10)
+                .vested_amount(1000, 0)
+                .unwrap_err(),
+            GiveawayError::ModeMismatch.into()
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use put_anchor_lang::prelude::*;
+use put_anchor_lang::put_program::ed25519_program;
+use put_anchor_lang::put_program::secp256k1_recover::secp256k1_recover;
+use put_anchor_lang::put_program::sysvar::instructions::load_instruction_at_checked;
+use sha3::{Digest, Keccak256};
+
+use crate::errors::GiveawayError;
+use crate::{ClaimSigner, SignatureFormat};
+
+// ed25519 预编译指令中每个签名的偏移量结构长度
+const ED25519_OFFSETS_SIZE: usize = 14;
+const ED25519_OFFSETS_START: usize = 2;
+
+// secp256k1 曲线阶 n 的一半，s 大于该值的签名可被改写为另一个有效签名
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+const EIP191_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
+const EIP712_DOMAIN_NAME: &str = "Giveaway";
+const EIP712_DOMAIN_VERSION: &str = "1";
+// 领取类型的公共字段，位图红包追加 uint32 index，普通红包再追加 uint128 amount
+// 签名金额的类型名为 Claim，否则为 ClaimWallet
+const EIP712_CLAIM_FIELDS: &str = "bytes32 wallet,address giveawayId,uint64 timestamp,uint64 nonce";
+
+// 领取签名的内容，领取序号只在位图红包中签名，金额只在普通红包中签名
+pub struct ClaimMessage {
+    pub wallet: [u8; 32],
+    pub giveaway_id: [u8; 20],
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub index: Option<u32>,
+    pub amount: Option<u128>,
+}
+
+impl ClaimMessage {
+    // 原始消息：钱包地址 + 红包ID + 截止时间 + nonce + 领取序号（可选） + 金额（可选），数字均为大端
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut message = [
+            self.wallet.as_ref(),
+            self.giveaway_id.as_ref(),
+            self.timestamp.to_be_bytes().as_ref(),
+            self.nonce.to_be_bytes().as_ref(),
+        ]
+        .concat();
+        if let Some(index) = self.index {
+            message.extend_from_slice(&index.to_be_bytes());
+        }
+        if let Some(amount) = self.amount {
+            message.extend_from_slice(&amount.to_be_bytes());
+        }
+        message
+    }
+
+    // EIP-712 类型字符串
+    fn type_string(&self) -> String {
+        let name = match self.amount {
+            Some(_) => "Claim",
+            None => "ClaimWallet",
+        };
+        let index = match self.index {
+            Some(_) => ",uint32 index",
+            None => "",
+        };
+        let amount = match self.amount {
+            Some(_) => ",uint128 amount",
+            None => "",
+        };
+        format!("{}({}{}{})", name, EIP712_CLAIM_FIELDS, index, amount)
+    }
+
+    // EIP-712 hashStruct，每个字段按 abi 编码为 32 字节
+    fn struct_hash(&self) -> [u8; 32] {
+        let type_hash = keccak(&[self.type_string().as_bytes()]);
+        let mut encoded = [type_hash.as_ref(), self.wallet.as_ref()].concat();
+        encoded.extend_from_slice(&abi_word(&self.giveaway_id));
+        encoded.extend_from_slice(&abi_word(&self.timestamp.to_be_bytes()));
+        encoded.extend_from_slice(&abi_word(&self.nonce.to_be_bytes()));
+        if let Some(index) = self.index {
+            encoded.extend_from_slice(&abi_word(&index.to_be_bytes()));
+        }
+        if let Some(amount) = self.amount {
+            encoded.extend_from_slice(&abi_word(&amount.to_be_bytes()));
+        }
+        keccak(&[&encoded])
+    }
+}
+
+// 校验领取签名，任意一个签名方通过即可；secp256k1 直接恢复公钥，ed25519 通过同一交易中的预编译指令校验
+pub fn verify_claim(
+    signers: &[ClaimSigner],
+    format: &SignatureFormat,
+    claim: &ClaimMessage,
+    signature: &[u8; 65],
+    instructions: &AccountInfo,
+) -> Result<()> {
+    let message = claim.to_bytes();
+    let ed25519_signed = signers.iter().any(|signer| match signer {
+        ClaimSigner::Ed25519 { pubkey } => {
+            verify_ed25519(pubkey, &message, &signature[..64], instructions)
+        }
+        ClaimSigner::Secp256k1 { .. } => false,
+    });
+    if ed25519_signed {
+        return Ok(());
+    }
+
+    let addresses: Vec<&[u8; 20]> = signers
+        .iter()
+        .filter_map(|signer| match signer {
+            ClaimSigner::Secp256k1 { address } => Some(address),
+            ClaimSigner::Ed25519 { .. } => None,
+        })
+        .collect();
+    require!(!addresses.is_empty(), GiveawayError::Forbidden);
+
+    // 签名格式错误时返回具体错误，恢复出的地址不在签名方中返回 Forbidden
+    let recovered = recover_secp256k1(&claim_digest(format, claim), signature)?;
+    require!(addresses.contains(&&recovered), GiveawayError::Forbidden);
+    Ok(())
+}
+
+// 按签名格式计算 secp256k1 签名的摘要
+fn claim_digest(format: &SignatureFormat, claim: &ClaimMessage) -> [u8; 32] {
+    let message = claim.to_bytes();
+    match *format {
+        SignatureFormat::Raw => keccak(&[&message]),
+        SignatureFormat::Eip191 => keccak(&[
+            EIP191_PREFIX.as_bytes(),
+            message.len().to_string().as_bytes(), // 必须是字符串len
+            &message,
+        ]),
+        SignatureFormat::Eip712 { chain_id } => keccak(&[
+            b"\x19\x01",
+            &domain_separator(chain_id),
+            &claim.struct_hash(),
+        ]),
+    }
+}
+
+// 域分隔符，salt 为本程序ID，防止签名被其他程序或链重放
+fn domain_separator(chain_id: u64) -> [u8; 32] {
+    keccak(&[
+        &keccak(&[EIP712_DOMAIN_TYPE.as_bytes()]),
+        &keccak(&[EIP712_DOMAIN_NAME.as_bytes()]),
+        &keccak(&[EIP712_DOMAIN_VERSION.as_bytes()]),
+        &abi_word(&chain_id.to_be_bytes()),
+        &crate::ID.to_bytes(),
+    ])
+}
+
+// 左侧补零到 32 字节
+fn abi_word(value: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[32 - value.len()..].copy_from_slice(value);
+    word
+}
+
+fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+// 恢复签名者的以太坊地址，v 支持 0/1 和 27/28，拒绝 s 在高半区的可延展签名
+fn recover_secp256k1(digest: &[u8; 32], signature: &[u8; 65]) -> Result<[u8; 20]> {
+    let recover_id = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        _ => return err!(GiveawayError::InvalidRecoveryId),
+    };
+    require!(
+        signature[32..64] <= SECP256K1_HALF_ORDER[..],
+        GiveawayError::InvalidSignature
+    );
+    let secp_pub = secp256k1_recover(digest, recover_id, &signature[..64])
+        .map_err(|_| error!(GiveawayError::InvalidSignature))?;
+
+    let signed_pub = keccak(&[&secp_pub.to_bytes()]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&signed_pub[12..]);
+    Ok(address)
+}
+
+fn verify_ed25519(
+    pubkey: &Pubkey,
+    message: &[u8],
+    signature: &[u8],
+    instructions: &AccountInfo,
+) -> bool {
+    let mut index = 0;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions) {
+        if instruction.program_id == ed25519_program::ID
+            && ed25519_instruction_contains(&instruction.data, pubkey.as_ref(), message, signature)
+        {
+            return true;
+        }
+        index += 1;
+    }
+    false
+}
+
+// ed25519 预编译指令数据：签名数量(1) + 填充(1) + 每个签名的偏移量
+// 只接受签名、公钥、消息都在该指令自身数据中的情况（instruction_index 为 u16::MAX）
+fn ed25519_instruction_contains(
+    data: &[u8],
+    pubkey: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let count = match data.first() {
+        Some(count) => *count as usize,
+        None => return false,
+    };
+
+    (0..count).any(|i| {
+        let start = ED25519_OFFSETS_START + i * ED25519_OFFSETS_SIZE;
+        let offsets = match data.get(start..start + ED25519_OFFSETS_SIZE) {
+            Some(offsets) => offsets,
+            None => return false,
+        };
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]) as usize;
+        let inline = u16::MAX as usize;
+        if read(2) != inline || read(6) != inline || read(12) != inline {
+            return false;
+        }
+
+        let slice = |offset: usize, len: usize| data.get(offset..offset + len);
+        slice(read(0), 64) == Some(signature)
+            && slice(read(4), 32) == Some(pubkey)
+            && read(10) == message.len()
+            && slice(read(8), message.len()) == Some(message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(index: Option<u32>, amount: Option<u128>) -> ClaimMessage {
+        ClaimMessage {
+            wallet: [1u8; 32],
+            giveaway_id: [2u8; 20],
+            timestamp: 1_700_000_000,
+            nonce: 42,
+            index,
+            amount,
+        }
+    }
+
+    // 按 EIP-712 规范逐字段编码，不复用 struct_hash
+    fn eip712_digest(chain_id: u64, claim: &ClaimMessage, type_string: &str) -> [u8; 32] {
+        let mut encoded = keccak(&[type_string.as_bytes()]).to_vec();
+        encoded.extend_from_slice(&claim.wallet);
+        encoded.extend_from_slice(&[0u8; 12]);
+        encoded.extend_from_slice(&claim.giveaway_id);
+        encoded.extend_from_slice(&[0u8; 24]);
+        encoded.extend_from_slice(&claim.timestamp.to_be_bytes());
+        encoded.extend_from_slice(&[0u8; 24]);
+        encoded.extend_from_slice(&claim.nonce.to_be_bytes());
+        if let Some(index) = claim.index {
+            encoded.extend_from_slice(&[0u8; 28]);
+            encoded.extend_from_slice(&index.to_be_bytes());
+        }
+        if let Some(amount) = claim.amount {
+            encoded.extend_from_slice(&[0u8; 16]);
+            encoded.extend_from_slice(&amount.to_be_bytes());
+        }
+
+        let mut domain =
+            keccak(&[b"EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)"])
+                .to_vec();
+        domain.extend_from_slice(&keccak(&[b"Giveaway"]));
+        domain.extend_from_slice(&keccak(&[b"1"]));
+        domain.extend_from_slice(&[0u8; 24]);
+        domain.extend_from_slice(&chain_id.to_be_bytes());
+        domain.extend_from_slice(crate::ID.as_ref());
+
+        keccak(&[b"\x19\x01", &keccak(&[&domain]), &keccak(&[&encoded])])
+    }
+
+    #[test]
+    fn eip712_digest_matches_typed_data_encoding() {
+        let format = SignatureFormat::Eip712 { chain_id: 1 };
+        let signed = claim(None, Some(500));
+        assert_eq!(
+            claim_digest(&format, &signed),
+            eip712_digest(
+                1,
+                &signed,
+                "Claim(bytes32 wallet,address giveawayId,uint64 timestamp,uint64 nonce,uint128 amount)"
+            )
+        );
+
+        let wallet_only = claim(None, None);
+        assert_eq!(
+            claim_digest(&format, &wallet_only),
+            eip712_digest(
+                1,
+                &wallet_only,
+                "ClaimWallet(bytes32 wallet,address giveawayId,uint64 timestamp,uint64 nonce)"
+            )
+        );
+
+        let indexed = claim(Some(3), None);
+        assert_eq!(
+            claim_digest(&format, &indexed),
+            eip712_digest(
+                1,
+                &indexed,
+                "ClaimWallet(bytes32 wallet,address giveawayId,uint64 timestamp,uint64 nonce,uint32 index)"
+            )
+        );
+    }
+
+    #[test]
+    fn eip712_digest_is_bound_to_chain_id() {
+        let signed = claim(None, Some(500));
+        assert_ne!(
+            claim_digest(&SignatureFormat::Eip712 { chain_id: 1 }, &signed),
+            claim_digest(&SignatureFormat::Eip712 { chain_id: 2 }, &signed)
+        );
+    }
+
+    #[test]
+    fn eip191_digest_prefixes_message_length() {
+        let signed = claim(None, Some(500));
+        let message = signed.to_bytes();
+        assert_eq!(message.len(), 84);
+        assert_eq!(
+            claim_digest(&SignatureFormat::Eip191, &signed),
+            keccak(&[b"\x19Ethereum Signed Message:\n84", &message])
+        );
+    }
+
+    #[test]
+    fn recover_secp256k1_rejects_malformed_signatures() {
+        let digest = [3u8; 32];
+        let mut signature = [1u8; 65];
+        signature[64] = 29;
+        assert_eq!(
+            recover_secp256k1(&digest, &signature).unwrap_err(),
+            GiveawayError::InvalidRecoveryId.into()
+        );
+
+        // s 在高半区
+        signature[64] = 27;
+        signature[32..64].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            recover_secp256k1(&digest, &signature).unwrap_err(),
+            GiveawayError::InvalidSignature.into()
+        );
+    }
+
+    // 与 ed25519 预编译指令相同的布局：公钥、签名、消息依次放在偏移量之后
+    fn ed25519_instruction(pubkey: &[u8], signature: &[u8], message: &[u8]) -> Vec<u8> {
+        let pubkey_offset = ED25519_OFFSETS_START + ED25519_OFFSETS_SIZE;
+        let signature_offset = pubkey_offset + 32;
+        let message_offset = signature_offset + 64;
+        let inline = u16::MAX;
+
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset as u16,
+            inline,
+            pubkey_offset as u16,
+            inline,
+            message_offset as u16,
+            message.len() as u16,
+            inline,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey);
+        data.extend_from_slice(signature);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn ed25519_instruction_contains_matching_signature() {
+        let (pubkey, signature) = ([4u8; 32], [5u8; 64]);
+        let message = claim(None, Some(500)).to_bytes();
+        let data = ed25519_instruction(&pubkey, &signature, &message);
+
+        assert!(ed25519_instruction_contains(
+            &data, &pubkey, &message, &signature
+        ));
+        assert!(!ed25519_instruction_contains(
+            &data, &[6u8; 32], &message, &signature
+        ));
+        assert!(!ed25519_instruction_contains(
+            &data,
+            &pubkey,
+            &message[1..],
+            &signature
+        ));
+        assert!(!ed25519_instruction_contains(
+            &data, &pubkey, &message, &[6u8; 64]
+        ));
+    }
+
+    #[test]
+    fn ed25519_instruction_contains_rejects_malformed_data() {
+        let (pubkey, signature) = ([4u8; 32], [5u8; 64]);
+        let message = claim(None, Some(500)).to_bytes();
+        let data = ed25519_instruction(&pubkey, &signature, &message);
+
+        assert!(!ed25519_instruction_contains(
+            &[],
+            &pubkey,
+            &message,
+            &signature
+        ));
+        // 数据被截断
+        assert!(!ed25519_instruction_contains(
+            &data[..data.len() - 1],
+            &pubkey,
+            &message,
+            &signature
+        ));
+        // 签名数量多于实际的偏移量结构时，仍能匹配前面的签名
+        let mut extra = data.clone();
+        extra[0] = 2;
+        assert!(ed25519_instruction_contains(
+            &extra, &pubkey, &message, &signature
+        ));
+        // 公钥引用其他指令的数据
+        let mut other_instruction = data;
+        other_instruction[ED25519_OFFSETS_START + 6] = 0;
+        assert!(!ed25519_instruction_contains(
+            &other_instruction,
+            &pubkey,
+            &message,
+            &signature
+        ));
+    }
+}
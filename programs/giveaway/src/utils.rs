@@ -1,5 +1,346 @@
 use put_anchor_lang::prelude::*;
+use put_anchor_lang::put_program::program::{invoke, invoke_signed};
+use put_anchor_lang::put_program::system_instruction;
+use put_anchor_ppl::associated_token;
+use put_anchor_ppl::token::ppl_token;
+use sha3::{Digest, Keccak256};
 
+use crate::errors::GiveawayError;
+#[cfg(feature = "legacy-log")]
+use crate::event_type::EventType;
+use crate::{CLAIMER_RECEIPT_SEED, CLAIM_RECEIPT_SEED, TOKEN_POOL_SEED};
+
+#[cfg(feature = "legacy-log")]
 pub fn log(content: String) {
     msg!("Giveaway {}", hex::encode(content));
 }
+
+// 旧版日志格式：事件类型,红包ID,金额,钱包地址,币种（主币为 0）
+#[cfg(feature = "legacy-log")]
+pub fn log_event(
+    event_type: EventType,
+    giveaway_id: &[u8; 20],
+    amount: u128,
+    wallet: &Pubkey,
+    token_mint: &Pubkey,
+) {
+    let token_mint = if *token_mint == Pubkey::default() {
+        String::from("0")
+    } else {
+        token_mint.to_string()
+    };
+
+    log(format!(
+        "{},{},{},{},{}",
+        event_type as u32,
+        hex::encode(giveaway_id),
+        amount,
+        wallet,
+        token_mint,
+    ));
+}
+
+// 拼手气红包的伪随机金额，返回 [0, range] 之间的值
+pub fn lucky_draw(seed: &[u8; 32], slot: u64, claim_index: u32, range: u128) -> u128 {
+    let mut hasher = Keccak256::new();
+    hasher.update(seed);
+    hasher.update(slot.to_le_bytes());
+    hasher.update(claim_index.to_le_bytes());
+    let hash = hasher.finalize();
+
+    let random = u128::from_le_bytes(hash[..16].try_into().unwrap());
+
+    match range.checked_add(1) {
+        Some(modulus) => random % modulus,
+        None => random,
+    }
+}
+
+// 白名单叶子：keccak256(钱包地址 + 金额大端 + 领取序号大端（位图红包）)
+pub fn merkle_leaf(wallet: &Pubkey, amount: u128, index: Option<u32>) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(wallet.as_ref());
+    hasher.update(amount.to_be_bytes());
+    if let Some(index) = index {
+        hasher.update(index.to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+// 按排序后的节点对逐层哈希，与 OpenZeppelin MerkleProof 一致
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let mut hasher = Keccak256::new();
+        if node <= *sibling {
+            hasher.update(node);
+            hasher.update(sibling);
+        } else {
+            hasher.update(sibling);
+            hasher.update(node);
+        }
+        hasher.finalize().into()
+    });
+    computed == root
+}
+
+// 从程序拥有的账户直接划转主币
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u128) -> Result<()> {
+    let final_from_amount = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(GiveawayError::ExceedError)?;
+    let final_to_amount = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(GiveawayError::ExceedError)?;
+
+    **from.lamports.borrow_mut() = final_from_amount;
+    **to.lamports.borrow_mut() = final_to_amount;
+    Ok(())
+}
+
+// 领取凭证 PDA 的地址和 bump
+pub fn claim_receipt_address(giveaway_id: &[u8; 20], nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLAIM_RECEIPT_SEED, giveaway_id, &nonce.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+// 领取人凭证 PDA 的地址和 bump
+pub fn claimer_receipt_address(giveaway_id: &[u8; 20], wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLAIMER_RECEIPT_SEED, giveaway_id, wallet.as_ref()],
+        &crate::ID,
+    )
+}
+
+// 创建领取凭证账户，账户已归本程序所有说明该 nonce 已被使用
+pub fn create_claim_receipt<'info>(
+    payer: &AccountInfo<'info>,
+    claim_receipt: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    giveaway_id: &[u8; 20],
+    nonce: u64,
+) -> Result<()> {
+    require!(claim_receipt.owner != &crate::ID, GiveawayError::NonceUsed);
+
+    let (_receipt, bump) = claim_receipt_address(giveaway_id, nonce);
+    create_receipt(
+        payer,
+        claim_receipt,
+        system_program,
+        &[
+            CLAIM_RECEIPT_SEED,
+            giveaway_id,
+            &nonce.to_le_bytes(),
+            &[bump],
+        ],
+    )
+}
+
+// 创建领取人凭证账户，账户已归本程序所有说明该钱包已领取
+pub fn create_claimer_receipt<'info>(
+    payer: &AccountInfo<'info>,
+    claimer_receipt: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    giveaway_id: &[u8; 20],
+    wallet: &Pubkey,
+) -> Result<()> {
+    require!(
+        claimer_receipt.owner != &crate::ID,
+        GiveawayError::AlreadyReceived
+    );
+
+    let (_receipt, bump) = claimer_receipt_address(giveaway_id, wallet);
+    create_receipt(
+        payer,
+        claimer_receipt,
+        system_program,
+        &[CLAIMER_RECEIPT_SEED, giveaway_id, wallet.as_ref(), &[bump]],
+    )
+}
+
+// 创建 0 字节的凭证账户，由 payer 支付租金
+// 用转账 + assign 而不是 create_account，避免他人预先转入租金后卡住该账户
+fn create_receipt<'info>(
+    payer: &AccountInfo<'info>,
+    receipt: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(0);
+    let required = rent.saturating_sub(receipt.lamports());
+    if required > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, receipt.key, required),
+            &[payer.clone(), receipt.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::assign(receipt.key, &crate::ID),
+        &[receipt.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    Ok(())
+}
+
+// 创建 authority 的关联代币账户，已存在时不做任何操作
+pub fn create_associated_token_account<'info>(
+    payer: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    associated_token: &AccountInfo<'info>,
+    token_mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(
+        associated_token.key()
+            == associated_token::get_associated_token_address(authority.key, token_mint.key),
+        GiveawayError::ArgsError
+    );
+    associated_token::create_idempotent(CpiContext::new(
+        associated_token_program.clone(),
+        associated_token::Create {
+            payer: payer.clone(),
+            associated_token: associated_token.clone(),
+            authority: authority.clone(),
+            mint: token_mint.clone(),
+            system_program: system_program.clone(),
+            token_program: token_program.clone(),
+        },
+    ))
+}
+
+// 红包代币池 PDA 的签名种子
+pub fn token_pool_signer(giveaway_id: &[u8; 20], token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TOKEN_POOL_SEED, giveaway_id, token_mint.as_ref()],
+        &crate::ID,
+    )
+}
+
+// 从红包代币池转出代币
+pub fn transfer_from_token_pool<'info>(
+    token_program: &AccountInfo<'info>,
+    token_pool: &AccountInfo<'info>,
+    to_account: &AccountInfo<'info>,
+    giveaway_id: &[u8; 20],
+    token_mint: &Pubkey,
+    amount: u128,
+) -> Result<()> {
+    let (_pool, bump) = token_pool_signer(giveaway_id, token_mint);
+
+    let transfer_instruction = ppl_token::instruction::transfer(
+        token_program.key,
+        token_pool.key,
+        to_account.key,
+        token_pool.key,
+        &[],
+        amount,
+    )?;
+    invoke_signed(
+        &transfer_instruction,
+        &[
+            token_program.clone(),
+            token_pool.clone(),
+            to_account.clone(),
+        ],
+        &[&[TOKEN_POOL_SEED, giveaway_id, token_mint.as_ref(), &[bump]]],
+    )?;
+    Ok(())
+}
+
+// 关闭红包代币池，租金退还给 destination
+pub fn close_token_pool<'info>(
+    token_program: &AccountInfo<'info>,
+    token_pool: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    giveaway_id: &[u8; 20],
+    token_mint: &Pubkey,
+) -> Result<()> {
+    let (_pool, bump) = token_pool_signer(giveaway_id, token_mint);
+
+    let close_instruction = ppl_token::instruction::close_account(
+        token_program.key,
+        token_pool.key,
+        destination.key,
+        token_pool.key,
+        &[],
+    )?;
+    invoke_signed(
+        &close_instruction,
+        &[
+            token_program.clone(),
+            token_pool.clone(),
+            destination.clone(),
+        ],
+        &[&[TOKEN_POOL_SEED, giveaway_id, token_mint.as_ref(), &[bump]]],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        let mut hasher = Keccak256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hasher.finalize().into()
+    }
+
+    #[test]
+    fn verify_merkle_proof_accepts_every_leaf() {
+        let wallets = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .enumerate()
+            .map(|(i, wallet)| merkle_leaf(wallet, 100 + i as u128, None))
+            .collect();
+        // 三个叶子：第三个叶子直接与前两个的父节点配对
+        let node = hash_pair(leaves[0], leaves[1]);
+        let root = hash_pair(node, leaves[2]);
+
+        assert!(verify_merkle_proof(
+            &[leaves[1], leaves[2]],
+            root,
+            leaves[0]
+        ));
+        assert!(verify_merkle_proof(
+            &[leaves[0], leaves[2]],
+            root,
+            leaves[1]
+        ));
+        assert!(verify_merkle_proof(&[node], root, leaves[2]));
+        assert!(verify_merkle_proof(&[], leaves[0], leaves[0]));
+    }
+
+    #[test]
+    fn verify_merkle_proof_rejects_wrong_leaf_or_proof() {
+        let wallet = Pubkey::new_unique();
+        let leaf = merkle_leaf(&wallet, 100, None);
+        let sibling = merkle_leaf(&Pubkey::new_unique(), 200, None);
+        let root = hash_pair(leaf, sibling);
+
+        assert!(!verify_merkle_proof(
+            &[sibling],
+            root,
+            merkle_leaf(&wallet, 101, None)
+        ));
+        assert!(!verify_merkle_proof(
+            &[sibling],
+            root,
+            merkle_leaf(&wallet, 100, Some(0))
+        ));
+        assert!(!verify_merkle_proof(&[leaf], root, leaf));
+        assert!(!verify_merkle_proof(&[], root, leaf));
+    }
+}
@@ -0,0 +1,45 @@
+use put_anchor_lang::prelude::*;
+
+#[cfg(feature = "legacy-log")]
+use crate::utils;
+
+#[event]
+pub struct PayNativeEvent {
+    pub order_id: String,
+    pub payer: Pubkey,
+    pub amount: u128,
+}
+
+#[event]
+pub struct PayTokenEvent {
+    pub order_id: String,
+    pub payer: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u128,
+}
+
+#[event]
+pub struct WithdrawEvent {
+    pub pay_is_main: bool,
+    pub from_account: Pubkey,
+    pub to_account: Pubkey,
+    pub amount: u128,
+}
+
+// 兼容旧版十六进制日志，迁移完成后移除
+#[cfg(feature = "legacy-log")]
+impl PayNativeEvent {
+    pub fn log(&self) {
+        utils::log(format!("{},{}", self.order_id, self.amount));
+    }
+}
+
+#[cfg(feature = "legacy-log")]
+impl PayTokenEvent {
+    pub fn log(&self) {
+        utils::log(format!(
+            "{},{},{}",
+            self.order_id, self.amount, self.token_mint
+        ));
+    }
+}
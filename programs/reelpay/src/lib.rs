@@ -1,4 +1,5 @@
 mod errors;
+mod events;
 mod utils;
 
 use put_anchor_lang::prelude::*;
@@ -9,6 +10,7 @@ use put_anchor_ppl::token::{
 };
 
 use errors::ReelPayError;
+use events::{PayNativeEvent, PayTokenEvent, WithdrawEvent};
 
 declare_id!("paytGwzjKgffkpCPPTzMbKJV1miozAjuXpzjZx6it5T");
 
@@ -39,7 +41,14 @@ pub mod reelpay {
                 ctx.accounts.system_program.to_account_info(),
             ],
         )?;
-        utils::log(format!("{},{}", args.order_id, args.amount));
+        let event = PayNativeEvent {
+            order_id: args.order_id,
+            payer: ctx.accounts.payer.key(),
+            amount: args.amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
 
         Ok(())
     }
@@ -67,12 +76,15 @@ pub mod reelpay {
                 ctx.accounts.payer.to_account_info(),
             ],
         )?;
-        utils::log(format!(
-            "{},{},{}",
-            args.order_id,
-            args.amount,
-            ctx.accounts.mint_account.key().to_string()
-        ));
+        let event = PayTokenEvent {
+            order_id: args.order_id,
+            payer: ctx.accounts.payer.key(),
+            token_mint: ctx.accounts.mint_account.key(),
+            amount: args.amount,
+        };
+        #[cfg(feature = "legacy-log")]
+        event.log();
+        emit!(event);
         Ok(())
     }
 
@@ -134,6 +146,13 @@ pub mod reelpay {
                 &[&[args.seed.as_bytes(), &[bump]]],
             )?;
         }
+
+        emit!(WithdrawEvent {
+            pay_is_main: args.pay_is_main,
+            from_account: ctx.accounts.from_account.key(),
+            to_account: ctx.accounts.to_account.key(),
+            amount: args.amount,
+        });
         Ok(())
     }
 
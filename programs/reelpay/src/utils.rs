@@ -1,5 +1,7 @@
+#[cfg(feature = "legacy-log")]
 use put_anchor_lang::prelude::*;
 
+#[cfg(feature = "legacy-log")]
 pub fn log(content: String) {
     msg!("ReelPay {}", hex::encode(content));
 }
@@ -1,12 +1,5 @@
 import * as anchor from "@com.put/put-anchor";
-import {
-  MessagePrefix,
-  concat,
-  ethers,
-  hashMessage,
-  keccak256,
-  toUtf8Bytes,
-} from "ethers";
+import { concat, ethers, keccak256 } from "ethers";
 import { Program } from "@com.put/put-anchor";
 import {
   TOKEN_PROGRAM_ID,
@@ -15,192 +8,1183 @@ import {
   getAssociatedTokenAddress,
 } from "@com.put/ppl-token";
 import type { Giveaway } from "../target/types/giveaway";
-import { bs58 } from "@com.put/put-anchor/dist/cjs/utils/bytes";
+import { assert } from "chai";
 
-const { PublicKey, SYSVAR_RENT_PUBKEY } = anchor.web3;
+const { PublicKey, SYSVAR_RENT_PUBKEY, SYSVAR_INSTRUCTIONS_PUBKEY } = anchor.web3;
 
 const USDT = "USDJASjwdezW9T1oCqDG2ui1PWrTt28QE1s7KBmaids";
 
+// secp256k1 曲线阶 n，用于构造 s 在高半区的签名
+const SECP256K1_N = new anchor.BN(
+  "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+  16
+);
+
+type CreateArgs = Parameters<
+  Program<Giveaway>["methods"]["createPutGiveaway"]
+>[0];
+
+// 红包ID即签名方的以太坊地址
+interface TestGiveaway {
+  signer: ethers.HDNodeWallet;
+  giveawayId: number[];
+  pool: anchor.web3.PublicKey;
+}
+
+// 领取内容，金额只在普通红包中签名，序号只在位图红包中签名
+interface Claim {
+  wallet: anchor.web3.PublicKey;
+  nonce: number;
+  amount?: number;
+  index?: number;
+}
+
 describe("Giveaway", () => {
   // Configure the client to use the local cluster.
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Giveaway as Program<Giveaway>;
+  const payer = provider.wallet.publicKey;
+  const usdtMint = new PublicKey(USDT);
 
-  it("createPutGiveaway", async () => {
-    const newWallet = ethers.Wallet.createRandom();
-    const putGiveawayId = newWallet.address;
+  // 签名截止时间
+  const deadline = parseInt(
+    (Number(new Date().setFullYear(2030)) / 1000).toFixed(0)
+  );
+  // 每次领取使用新的 nonce
+  let nonce = Date.now();
+  const nextNonce = () => nonce++;
+
+  // 创建者的 USDT 关联账户
+  let usdtAccount: anchor.web3.PublicKey;
+  before(async () => {
+    usdtAccount = await getAssociatedTokenAddress(usdtMint, payer);
+  });
+
+  const newGiveaway = async (): Promise<TestGiveaway> => {
+    const signer = ethers.Wallet.createRandom();
+    const id = ethers.getBytes(signer.address);
+    const [pool] = await PublicKey.findProgramAddress([id], program.programId);
+    return { signer, giveawayId: Array.from(id), pool };
+  };
+
+  const tokenPoolOf = async (giveaway: TestGiveaway) => {
+    const [tokenPool] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("token_pool"),
+        Buffer.from(giveaway.giveawayId),
+        usdtMint.toBytes(),
+      ],
+      program.programId
+    );
+    return tokenPool;
+  };
+
+  const claimReceiptOf = async (
+    giveaway: TestGiveaway,
+    claimNonce: number
+  ) => {
+    const [claimReceipt] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("claim_receipt"),
+        Buffer.from(giveaway.giveawayId),
+        new anchor.BN(claimNonce).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    return claimReceipt;
+  };
 
-    const [giveaway_pool] = await PublicKey.findProgramAddress(
-      [ethers.toBeArray(putGiveawayId)],
+  const claimerReceiptOf = async (
+    giveaway: TestGiveaway,
+    wallet: anchor.web3.PublicKey
+  ) => {
+    const [claimerReceipt] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("claimer_receipt"),
+        Buffer.from(giveaway.giveawayId),
+        wallet.toBytes(),
+      ],
       program.programId
     );
+    return claimerReceipt;
+  };
+
+  const vestingAccountOf = async (giveaway: TestGiveaway) => {
+    const [vestingAccount] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("vesting"),
+        Buffer.from(giveaway.giveawayId),
+        payer.toBytes(),
+      ],
+      program.programId
+    );
+    return vestingAccount;
+  };
+
+  // 原始消息：钱包 + 红包ID + 截止时间 + nonce + 领取序号（可选） + 金额（可选）
+  const claimMessage = (giveaway: TestGiveaway, claim: Claim) => {
+    const types = ["bytes32", "address", "uint64", "uint64"];
+    const values: (Uint8Array | string | number)[] = [
+      claim.wallet.toBytes(),
+      giveaway.signer.address,
+      deadline,
+      claim.nonce,
+    ];
+    if (claim.index !== undefined) {
+      types.push("uint32");
+      values.push(claim.index);
+    }
+    if (claim.amount !== undefined) {
+      types.push("uint128");
+      values.push(claim.amount);
+    }
+    return ethers.solidityPacked(types, values);
+  };
+
+  // raw 格式：对 keccak256(消息) 直接签名，默认由红包ID对应的钱包签名
+  const signClaim = (
+    giveaway: TestGiveaway,
+    claim: Claim,
+    signer: ethers.HDNodeWallet = giveaway.signer
+  ) =>
+    Array.from<number>(
+      ethers.getBytes(
+        signer.signingKey.sign(keccak256(claimMessage(giveaway, claim)))
+          .serialized
+      )
+    );
+
+  // 白名单叶子 keccak256(钱包地址 + 金额)，按排序后的节点对逐层哈希
+  const leafOf = (wallet: anchor.web3.PublicKey, value: number) =>
+    keccak256(
+      ethers.solidityPacked(["bytes32", "uint128"], [wallet.toBytes(), value])
+    );
+  const hashPair = (a: string, b: string) =>
+    keccak256(concat(a < b ? [a, b] : [b, a]));
+
+  // 默认为 10 份、共 10 主币的普通红包，签名方为红包ID对应的钱包
+  const createArgs = (
+    giveaway: TestGiveaway,
+    args: Partial<CreateArgs>
+  ): CreateArgs => ({
+    giveawayId: giveaway.giveawayId,
+    giveawayCount: 10,
+    amount: new anchor.BN(10000000000),
+    expireTime: null,
+    startTime: null,
+    signers: [
+      {
+        secp256k1: {
+          address: Array.from(ethers.getBytes(giveaway.signer.address)),
+        },
+      },
+    ],
+    signatureFormat: { raw: {} },
+    recordMode: { list: {} },
+    mode: { signed: {} },
+    ...args,
+  });
+
+  const createPutGiveaway = (
+    giveaway: TestGiveaway,
+    args: Partial<CreateArgs> = {}
+  ) =>
+    program.methods.createPutGiveaway(createArgs(giveaway, args)).accounts({
+      payer,
+      giveawayPool: giveaway.pool,
+      systemProgram: SYSTEM_PROGRAM_ID,
+    });
+
+  const createNonPutGiveaway = async (
+    giveaway: TestGiveaway,
+    args: Partial<CreateArgs> = {}
+  ) =>
+    program.methods
+      .createNonPutGiveaway(
+        createArgs(giveaway, { amount: new anchor.BN(1000000), ...args })
+      )
+      .accounts({
+        systemProgram: SYSTEM_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        payer,
+        giveawayPool: giveaway.pool,
+        fromAccount: usdtAccount,
+        tokenMint: usdtMint,
+        tokenPool: await tokenPoolOf(giveaway),
+      })
+      .rpc();
+
+  // 每份 amount 主币 + tokenAmount USDT，创建和加入代币放在同一笔交易中，避免加入前被领取
+  const createBundleGiveaway = async (
+    giveaway: TestGiveaway,
+    giveawayCount: number,
+    amount: number,
+    tokenAmount: number
+  ) => {
+    const createIx = await createPutGiveaway(giveaway, {
+      giveawayCount,
+      amount: new anchor.BN(amount * giveawayCount),
+      mode: { bundle: { amount: new anchor.BN(amount) } },
+    }).instruction();
+
+    return program.methods
+      .addBundleAsset({
+        giveawayId: giveaway.giveawayId,
+        amount: new anchor.BN(tokenAmount),
+      })
+      .accounts({
+        systemProgram: SYSTEM_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        payer,
+        giveawayPool: giveaway.pool,
+        fromAccount: usdtAccount,
+        tokenPool: await tokenPoolOf(giveaway),
+        tokenMint: usdtMint,
+      })
+      .preInstructions([createIx])
+      .rpc();
+  };
+
+  const receivePut = async (
+    giveaway: TestGiveaway,
+    claim: Claim,
+    signature: number[],
+    preInstructions: anchor.web3.TransactionInstruction[] = []
+  ) =>
+    program.methods
+      .receivePutGiveaway({
+        giveawayId: giveaway.giveawayId,
+        walletAddress: Array.from<number>(claim.wallet.toBytes()),
+        amount: new anchor.BN(claim.amount ?? 0),
+        timestamp: new anchor.BN(deadline),
+        nonce: new anchor.BN(claim.nonce),
+        index: claim.index ?? null,
+        signature,
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        claimReceipt: await claimReceiptOf(giveaway, claim.nonce),
+        claimerReceipt: await claimerReceiptOf(giveaway, claim.wallet),
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions(preInstructions)
+      .rpc();
+
+  const cancelGiveaway = (giveaway: TestGiveaway) =>
+    program.methods
+      .cancelGiveaway({ giveawayId: giveaway.giveawayId })
+      .accounts({ payer, giveawayPool: giveaway.pool })
+      .rpc();
+
+  const refundPut = (giveaway: TestGiveaway) =>
+    program.methods
+      .refundPut({ giveawayId: giveaway.giveawayId })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .rpc();
+
+  const refundToken = async (giveaway: TestGiveaway) =>
+    program.methods
+      .refundToken({ giveawayId: giveaway.giveawayId })
+      .accounts({
+        systemProgram: SYSTEM_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        payer,
+        giveawayPool: giveaway.pool,
+        tokenPool: await tokenPoolOf(giveaway),
+        toAccount: usdtAccount,
+        tokenMint: usdtMint,
+      })
+      .rpc();
+
+  // 组合红包退款和关闭时每种代币传入 [代币池, 创建者代币账户]
+  const bundleRefundAccounts = async (giveaway: TestGiveaway) => [
+    { pubkey: await tokenPoolOf(giveaway), isSigner: false, isWritable: true },
+    { pubkey: usdtAccount, isSigner: false, isWritable: true },
+  ];
+
+  const refundBundle = async (giveaway: TestGiveaway) =>
+    program.methods
+      .refundBundle({ giveawayId: giveaway.giveawayId })
+      .accounts({
+        systemProgram: SYSTEM_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        payer,
+        giveawayPool: giveaway.pool,
+      })
+      .remainingAccounts(await bundleRefundAccounts(giveaway))
+      .rpc();
+
+  const fetchPool = (giveaway: TestGiveaway) =>
+    program.account.giveawayPool.fetch(giveaway.pool);
+
+  const lamportsOf = (address: anchor.web3.PublicKey) =>
+    provider.connection.getBalance(address);
+
+  const tokenBalanceOf = async (address: anchor.web3.PublicKey) =>
+    Number(
+      (await provider.connection.getTokenAccountBalance(address)).value.amount
+    );
+
+  const chainTime = async () => {
+    const slot = await provider.connection.getSlot();
+    return Number(await provider.connection.getBlockTime(slot));
+  };
+
+  // 等到链上时间超过 timestamp
+  const waitUntil = async (timestamp: number) => {
+    while ((await chainTime()) <= timestamp) {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+  };
+
+  // 交易必须以指定的程序错误失败
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    let error: unknown;
     try {
-      const tx = await program.methods
-        .createPutGiveaway({
-          giveawayId: Array.from(ethers.toBeArray(putGiveawayId)),
-          giveawayCount: 10,
-          amount: new anchor.BN(10000000000),
-        })
-        .accounts({
-          payer: provider.wallet.publicKey,
-          giveawayPool: giveaway_pool,
-          systemProgram: SYSTEM_PROGRAM_ID,
-        })
-        .rpc();
-      console.log("Your transaction signature", tx);
-    } catch (error) {
-      console.error(error);
+      await tx;
+    } catch (e) {
+      error = e;
     }
+    assert.instanceOf(error, anchor.AnchorError, `expected ${code}`);
+    assert.equal((error as anchor.AnchorError).error.errorCode.code, code);
+  };
+
+  it("createPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    const pool = await fetchPool(giveaway);
+    assert.isTrue(pool.creator.equals(payer));
+    assert.equal(pool.totalAmount.toNumber(), 10000000000);
+    assert.equal(pool.remainingAmount.toNumber(), 10000000000);
+    assert.equal(pool.giveawayCount, 10);
+    assert.equal(pool.claimedCount, 0);
+  });
+
+  it("createLuckyPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    const [minAmount, maxAmount] = [100000000, 3000000000];
+    await createPutGiveaway(giveaway, {
+      mode: {
+        lucky: {
+          seed: Array.from(ethers.randomBytes(32)),
+          minAmount: new anchor.BN(minAmount),
+          maxAmount: new anchor.BN(maxAmount),
+        },
+      },
+    }).rpc();
+
+    // 金额由程序计算，签名中不包含金额
+    const claim = { wallet: payer, nonce: nextNonce() };
+    const balance = await lamportsOf(giveaway.pool);
+    await receivePut(giveaway, claim, signClaim(giveaway, claim));
+
+    const pool = await fetchPool(giveaway);
+    const amount = pool.claimedAmount.toNumber();
+    assert.isAtLeast(amount, minAmount);
+    assert.isAtMost(amount, maxAmount);
+    assert.equal(balance - (await lamportsOf(giveaway.pool)), amount);
+    assert.equal(pool.remainingAmount.toNumber(), 10000000000 - amount);
+  });
+
+  it("createEqualPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway, { mode: { equal: {} } }).rpc();
+
+    const claim = { wallet: payer, nonce: nextNonce() };
+    await receivePut(giveaway, claim, signClaim(giveaway, claim));
+
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.claimedAmount.toNumber(), 1000000000);
+    assert.equal(pool.claimedCount, 1);
+  });
+
+  it("createScheduledPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    // 一小时后开始，开始前领取会返回 NotStarted
+    const startTime = (await chainTime()) + 3600;
+    await createPutGiveaway(giveaway, {
+      startTime: new anchor.BN(startTime),
+      mode: { equal: {} },
+    }).rpc();
+
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.startTime?.toNumber(), startTime);
+
+    const claim = { wallet: payer, nonce: nextNonce() };
+    await expectError(
+      receivePut(giveaway, claim, signClaim(giveaway, claim)),
+      "NotStarted"
+    );
   });
 
   it("receivePutGiveaway", async () => {
-    const phrase =
-      "midnight embrace host earn disorder leave twice evolve fresh spot season doll";
-    const newWallet = ethers.Wallet.fromPhrase(phrase);
-    const putGiveawayId = newWallet.address;
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway, {
+      signatureFormat: { eip191: {} },
+    }).rpc();
 
-    const [giveaway_pool] = await PublicKey.findProgramAddress(
-      [ethers.toBeArray(putGiveawayId)],
-      program.programId
+    const amount = 1000000000;
+    const claim = { wallet: payer, nonce: nextNonce(), amount };
+    // personal_sign 签名，红包需以 eip191 格式创建
+    const signature = Array.from<number>(
+      ethers.getBytes(
+        await giveaway.signer.signMessage(
+          ethers.getBytes(claimMessage(giveaway, claim))
+        )
+      )
     );
-    try {
-      const receiveAddress = provider.wallet.publicKey;
-      const amount = 1000000000;
-      const timestamp = new Date().setFullYear(2024);
-      const origin_message = ethers.solidityPacked(
-        ["address", "bytes32", "uint64", "uint128"],
-        [putGiveawayId, receiveAddress.toBytes(), amount, timestamp]
-      );
-      // const origin_message = putGiveawayId + receiveAddress.toString() + amount + timestamp;
-      const signature = await newWallet.signMessage(
-        ethers.toBeArray(origin_message)
+
+    const balance = await lamportsOf(giveaway.pool);
+    await receivePut(giveaway, claim, signature);
+    assert.equal(balance - (await lamportsOf(giveaway.pool)), amount);
+
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.remainingAmount.toNumber(), 10000000000 - amount);
+    assert.equal(pool.claimedCount, 1);
+    assert.isTrue(pool.receiveRecords[0].equals(payer));
+
+    // 同一签名不能重放
+    await expectError(receivePut(giveaway, claim, signature), "NonceUsed");
+
+    // 同一钱包换一个 nonce 也不能再次领取
+    const again = { wallet: payer, nonce: nextNonce(), amount };
+    const againSignature = Array.from<number>(
+      ethers.getBytes(
+        await giveaway.signer.signMessage(
+          ethers.getBytes(claimMessage(giveaway, again))
+        )
+      )
+    );
+    await expectError(
+      receivePut(giveaway, again, againSignature),
+      "AlreadyReceived"
+    );
+  });
+
+  it("receivePutGiveawayEip712", async () => {
+    const giveaway = await newGiveaway();
+    const chainId = 1;
+    await createPutGiveaway(giveaway, {
+      signatureFormat: { eip712: { chainId: new anchor.BN(chainId) } },
+    }).rpc();
+
+    const amount = 1000000000;
+    const claim = { wallet: payer, nonce: nextNonce(), amount };
+    // 域的 salt 为程序ID
+    const signature = await giveaway.signer.signTypedData(
+      {
+        name: "Giveaway",
+        version: "1",
+        chainId,
+        salt: program.programId.toBytes(),
+      },
+      {
+        Claim: [
+          { name: "wallet", type: "bytes32" },
+          { name: "giveawayId", type: "address" },
+          { name: "timestamp", type: "uint64" },
+          { name: "nonce", type: "uint64" },
+          { name: "amount", type: "uint128" },
+        ],
+      },
+      {
+        wallet: payer.toBytes(),
+        giveawayId: giveaway.signer.address,
+        timestamp: deadline,
+        nonce: claim.nonce,
+        amount,
+      }
+    );
+
+    const balance = await lamportsOf(giveaway.pool);
+    await receivePut(
+      giveaway,
+      claim,
+      Array.from<number>(ethers.getBytes(signature))
+    );
+    assert.equal(balance - (await lamportsOf(giveaway.pool)), amount);
+  });
+
+  it("rejectMalformedSignature", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    const claim = { wallet: payer, nonce: nextNonce(), amount: 1000000000 };
+    const signature = signClaim(giveaway, claim);
+
+    // v 只能是 0/1 或 27/28
+    await expectError(
+      receivePut(giveaway, claim, [...signature.slice(0, 64), 29]),
+      "InvalidRecoveryId"
+    );
+
+    // (r, n - s) 同样能恢复出签名方，但 s 在高半区，必须拒绝
+    const highS = SECP256K1_N.sub(new anchor.BN(signature.slice(32, 64)));
+    const v = signature[64] === 27 ? 28 : 27;
+    await expectError(
+      receivePut(giveaway, claim, [
+        ...signature.slice(0, 32),
+        ...highS.toArray("be", 32),
+        v,
+      ]),
+      "InvalidSignature"
+    );
+
+    // 不是签名方的钱包签名
+    const other = ethers.Wallet.createRandom();
+    await expectError(
+      receivePut(giveaway, claim, signClaim(giveaway, claim, other)),
+      "Forbidden"
+    );
+  });
+
+  it("batchReceivePutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    const amount = 1000000000;
+    const wallets = [];
+    const entries = [];
+    const remainingAccounts = [];
+    // 中继替多个钱包领取，每个钱包后面跟着对应的领取凭证账户
+    for (let i = 0; i < 3; i++) {
+      const wallet = anchor.web3.Keypair.generate().publicKey;
+      const claim = { wallet, nonce: nextNonce(), amount };
+      wallets.push(wallet);
+      entries.push({
+        walletAddress: Array.from<number>(wallet.toBytes()),
+        amount: new anchor.BN(amount),
+        timestamp: new anchor.BN(deadline),
+        nonce: new anchor.BN(claim.nonce),
+        index: null,
+        signature: signClaim(giveaway, claim),
+      });
+      remainingAccounts.push(
+        { pubkey: wallet, isSigner: false, isWritable: true },
+        {
+          pubkey: await claimReceiptOf(giveaway, claim.nonce),
+          isSigner: false,
+          isWritable: true,
+        }
       );
+    }
+
+    await program.methods
+      .batchReceivePutGiveaway({
+        giveawayId: giveaway.giveawayId,
+        entries,
+        skipInvalid: false,
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+
+    for (const wallet of wallets) {
+      assert.equal(await lamportsOf(wallet), amount);
+    }
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.claimedCount, 3);
+    assert.equal(pool.claimedAmount.toNumber(), amount * 3);
+  });
+
+  it("receivePutGiveawayEd25519", async () => {
+    const giveaway = await newGiveaway();
+    const signer = anchor.web3.Keypair.generate();
+    await createPutGiveaway(giveaway, {
+      signers: [{ ed25519: { pubkey: signer.publicKey } }],
+    }).rpc();
+
+    const amount = 1000000000;
+    const claim = { wallet: payer, nonce: nextNonce(), amount };
+    // ed25519 签名由交易中的预编译指令校验，程序只核对签名内容
+    const ed25519Instruction =
+      anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message: ethers.getBytes(claimMessage(giveaway, claim)),
+      });
+    const signature = ed25519Instruction.data.subarray(16 + 32, 16 + 32 + 64);
+
+    const balance = await lamportsOf(giveaway.pool);
+    await receivePut(
+      giveaway,
+      claim,
+      [...Array.from<number>(signature), 0],
+      [ed25519Instruction]
+    );
+    assert.equal(balance - (await lamportsOf(giveaway.pool)), amount);
+  });
 
-      const tx = await program.methods
-        .receivePutGiveaway({
-          giveawayId: Array.from(ethers.toBeArray(putGiveawayId)),
-          walletAddress: Array.from<number>(receiveAddress.toBytes()),
-          amount: new anchor.BN(amount),
-          timestamp: new anchor.BN(timestamp),
-          signature: Array.from<number>(ethers.toBeArray(signature)),
+  it("receivePutGiveawayWithProof", async () => {
+    const giveaway = await newGiveaway();
+    const amount = 1000000000;
+    const leaf = leafOf(payer, amount);
+    const sibling = leafOf(anchor.web3.Keypair.generate().publicKey, amount);
+    const root = hashPair(leaf, sibling);
+
+    // 凭证模式：红包账户只记录计数，每个领取钱包生成一个领取人凭证
+    await createPutGiveaway(giveaway, {
+      giveawayCount: 2,
+      amount: new anchor.BN(amount * 2),
+      // 白名单红包不需要签名方
+      signers: [],
+      recordMode: { receipt: {} },
+      mode: { merkle: { root: Array.from(ethers.getBytes(root)) } },
+    }).rpc();
+
+    const receiveWithProof = async (value: number) =>
+      program.methods
+        .receivePutGiveawayWithProof({
+          giveawayId: giveaway.giveawayId,
+          amount: new anchor.BN(value),
+          index: null,
+          proof: [Array.from(ethers.getBytes(sibling))],
         })
         .accounts({
-          payer: provider.wallet.publicKey,
-          giveawayPool: giveaway_pool,
+          payer,
+          giveawayPool: giveaway.pool,
           systemProgram: SYSTEM_PROGRAM_ID,
+          claimerReceipt: await claimerReceiptOf(giveaway, payer),
         })
         .rpc();
-      console.log("Your transaction signature", tx);
-    } catch (error) {
-      console.error(error);
-    }
+
+    // 叶子中的金额不符
+    await expectError(receiveWithProof(amount * 2), "InvalidProof");
+
+    const balance = await lamportsOf(giveaway.pool);
+    await receiveWithProof(amount);
+    assert.equal(balance - (await lamportsOf(giveaway.pool)), amount);
+
+    await expectError(receiveWithProof(amount), "AlreadyReceived");
   });
 
-  it("createNonPutGiveaway", async () => {
-    const newWallet = ethers.Wallet.createRandom();
-    const putGiveawayId = newWallet.address;
+  it("claimVestedPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    // 额度叶子 keccak256(钱包地址 + 额度)，与白名单红包相同
+    const allocation = 1000000000;
+    const leaf = leafOf(payer, allocation);
+    const sibling = leafOf(
+      anchor.web3.Keypair.generate().publicKey,
+      allocation
+    );
+    const root = hashPair(leaf, sibling);
+    const vestingAccount = await vestingAccountOf(giveaway);
 
-    const [giveaway_pool] = await PublicKey.findProgramAddress(
-      [ethers.toBeArray(putGiveawayId)],
-      program.programId
+    // 从一分钟前开始，一小时内线性释放，无 cliff
+    const now = await chainTime();
+    await createPutGiveaway(giveaway, {
+      giveawayCount: 2,
+      amount: new anchor.BN(allocation * 2),
+      recordMode: { receipt: {} },
+      mode: {
+        vesting: {
+          startTime: new anchor.BN(now - 60),
+          cliffTime: new anchor.BN(now - 60),
+          endTime: new anchor.BN(now + 3600),
+          root: Array.from(ethers.getBytes(root)),
+        },
+      },
+    }).rpc();
+
+    // 首次领取登记额度，之后不再需要证明
+    await program.methods
+      .claimVestedPut({
+        giveawayId: giveaway.giveawayId,
+        allocation: new anchor.BN(allocation),
+        proof: { merkle: { proof: [Array.from(ethers.getBytes(sibling))] } },
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        vestingAccount,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .rpc();
+
+    const vesting = await program.account.vestingAccount.fetch(vestingAccount);
+    assert.equal(vesting.allocation.toNumber(), allocation);
+    assert.isAbove(vesting.withdrawn.toNumber(), 0);
+    assert.isBelow(vesting.withdrawn.toNumber(), allocation);
+
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.allocatedAmount.toNumber(), allocation);
+    assert.equal(pool.claimedAmount.toNumber(), vesting.withdrawn.toNumber());
+  });
+
+  it("registerVestedBeforeCliff", async () => {
+    const giveaway = await newGiveaway();
+    const allocation = 1000000000;
+    const leaf = leafOf(payer, allocation);
+    const sibling = leafOf(
+      anchor.web3.Keypair.generate().publicKey,
+      allocation
     );
+    const root = hashPair(leaf, sibling);
+    const vestingAccount = await vestingAccountOf(giveaway);
 
-    const [token_pool] = await PublicKey.findProgramAddress(
-      [provider.wallet.publicKey.toBytes(), new PublicKey(USDT).toBytes()],
-      program.programId
+    // 一小时后才到 cliff，登记时没有可领取的金额
+    const now = await chainTime();
+    await createPutGiveaway(giveaway, {
+      giveawayCount: 2,
+      amount: new anchor.BN(allocation * 2),
+      recordMode: { receipt: {} },
+      mode: {
+        vesting: {
+          startTime: new anchor.BN(now + 3600),
+          cliffTime: new anchor.BN(now + 3600),
+          endTime: new anchor.BN(now + 7200),
+          root: Array.from(ethers.getBytes(root)),
+        },
+      },
+    }).rpc();
+
+    await program.methods
+      .claimVestedPut({
+        giveawayId: giveaway.giveawayId,
+        allocation: new anchor.BN(allocation),
+        proof: { merkle: { proof: [Array.from(ethers.getBytes(sibling))] } },
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        vestingAccount,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .rpc();
+
+    const vesting = await program.account.vestingAccount.fetch(vestingAccount);
+    assert.equal(vesting.allocation.toNumber(), allocation);
+    assert.equal(vesting.withdrawn.toNumber(), 0);
+
+    // 未过期的红包可以随时退款，但已登记的额度会被保留
+    await refundPut(giveaway);
+
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.remainingAmount.toNumber(), allocation);
+  });
+
+  it("expirePutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    const expireTime = (await chainTime()) + 10;
+    await createPutGiveaway(giveaway, {
+      expireTime: new anchor.BN(expireTime),
+    }).rpc();
+
+    // 有过期时间的红包，过期前不能退款
+    await expectError(refundPut(giveaway), "NotExpired");
+
+    await waitUntil(expireTime);
+
+    const claim = { wallet: payer, nonce: nextNonce(), amount: 1000000000 };
+    await expectError(
+      receivePut(giveaway, claim, signClaim(giveaway, claim)),
+      "Expired"
     );
 
-    // 找到一个USDT的关联账户
-    const usdt_ass = await getAssociatedTokenAddress(
-      new PublicKey(USDT),
-      provider.wallet.publicKey
+    const balance = await lamportsOf(giveaway.pool);
+    await refundPut(giveaway);
+    assert.equal(balance - (await lamportsOf(giveaway.pool)), 10000000000);
+    assert.equal((await fetchPool(giveaway)).remainingAmount.toNumber(), 0);
+  });
+
+  it("refundPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    const amount = 1000000000;
+    const claim = { wallet: payer, nonce: nextNonce(), amount };
+    await receivePut(giveaway, claim, signClaim(giveaway, claim));
+
+    // 只有创建者可以退款，退还剩余部分
+    await cancelGiveaway(giveaway);
+    const balance = await lamportsOf(giveaway.pool);
+    await refundPut(giveaway);
+    assert.equal(
+      balance - (await lamportsOf(giveaway.pool)),
+      10000000000 - amount
     );
 
-    try {
-      const tx = await program.methods
-        .createNonPutGiveaway({
-          giveawayId: Array.from(ethers.toBeArray(putGiveawayId)),
-          giveawayCount: 10,
-          amount: new anchor.BN(1000000),
-        })
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.remainingAmount.toNumber(), 0);
+    assert.equal(pool.claimedAmount.toNumber(), amount);
+  });
+
+  it("createNonPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    const balance = await tokenBalanceOf(usdtAccount);
+    await createNonPutGiveaway(giveaway);
+
+    assert.equal(balance - (await tokenBalanceOf(usdtAccount)), 1000000);
+    assert.equal(await tokenBalanceOf(await tokenPoolOf(giveaway)), 1000000);
+    const pool = await fetchPool(giveaway);
+    assert.isTrue(pool.tokenMint.equals(usdtMint));
+    assert.equal(pool.remainingAmount.toNumber(), 1000000);
+  });
+
+  it("receiveNonPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createNonPutGiveaway(giveaway);
+
+    // 中继代付手续费，收款钱包的关联代币账户不存在时自动创建
+    const wallet = anchor.web3.Keypair.generate().publicKey;
+    const toAccount = await getAssociatedTokenAddress(usdtMint, wallet);
+    const amount = 300000;
+    const claim = { wallet, nonce: nextNonce(), amount };
+
+    await program.methods
+      .receiveNonPutGiveaway({
+        giveawayId: giveaway.giveawayId,
+        // 收款代币账户的所有者，payer 可以是代付手续费的中继
+        walletAddress: Array.from<number>(wallet.toBytes()),
+        amount: new anchor.BN(amount),
+        timestamp: new anchor.BN(deadline),
+        nonce: new anchor.BN(claim.nonce),
+        index: null,
+        signature: signClaim(giveaway, claim),
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        claimReceipt: await claimReceiptOf(giveaway, claim.nonce),
+        claimerReceipt: await claimerReceiptOf(giveaway, wallet),
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMint: usdtMint,
+        tokenPool: await tokenPoolOf(giveaway),
+        toAccount,
+        wallet,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    assert.equal(await tokenBalanceOf(toAccount), amount);
+    assert.equal(
+      await tokenBalanceOf(await tokenPoolOf(giveaway)),
+      1000000 - amount
+    );
+  });
+
+  it("refundTokenGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    const expireTime = (await chainTime()) + 10;
+    await createNonPutGiveaway(giveaway, {
+      expireTime: new anchor.BN(expireTime),
+    });
+
+    await expectError(refundToken(giveaway), "NotExpired");
+    await waitUntil(expireTime);
+
+    const balance = await tokenBalanceOf(usdtAccount);
+    await refundToken(giveaway);
+    assert.equal((await tokenBalanceOf(usdtAccount)) - balance, 1000000);
+    assert.equal(await tokenBalanceOf(await tokenPoolOf(giveaway)), 0);
+    assert.equal((await fetchPool(giveaway)).remainingAmount.toNumber(), 0);
+  });
+
+  it("createBundleGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    // 每份 0.1 主币 + 1 USDT，共 5 份
+    await createBundleGiveaway(giveaway, 5, 100000000, 1000000);
+
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.remainingAmount.toNumber(), 500000000);
+    assert.equal(pool.bundle.length, 1);
+    assert.isTrue(pool.bundle[0].tokenMint.equals(usdtMint));
+    assert.equal(pool.bundle[0].remainingAmount.toNumber(), 5000000);
+    assert.equal(await tokenBalanceOf(await tokenPoolOf(giveaway)), 5000000);
+  });
+
+  it("receiveBundleGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    const [amount, tokenAmount] = [100000000, 1000000];
+    await createBundleGiveaway(giveaway, 5, amount, tokenAmount);
+
+    // 组合红包每份数量固定，签名中不包含金额
+    const claim = { wallet: payer, nonce: nextNonce() };
+    const tokenPool = await tokenPoolOf(giveaway);
+    const poolBefore = await lamportsOf(giveaway.pool);
+    const tokenBefore = await tokenBalanceOf(usdtAccount);
+
+    // 每种代币传入 [代币池, 领取人关联代币账户, 代币]
+    await program.methods
+      .receiveBundleGiveaway({
+        giveawayId: giveaway.giveawayId,
+        walletAddress: Array.from<number>(payer.toBytes()),
+        timestamp: new anchor.BN(deadline),
+        nonce: new anchor.BN(claim.nonce),
+        index: null,
+        signature: signClaim(giveaway, claim),
+      })
+      .accounts({
+        systemProgram: SYSTEM_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        payer,
+        giveawayPool: giveaway.pool,
+        claimReceipt: await claimReceiptOf(giveaway, claim.nonce),
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        claimerReceipt: await claimerReceiptOf(giveaway, payer),
+      })
+      .remainingAccounts([
+        { pubkey: tokenPool, isSigner: false, isWritable: true },
+        { pubkey: usdtAccount, isSigner: false, isWritable: true },
+        { pubkey: usdtMint, isSigner: false, isWritable: false },
+      ])
+      .rpc();
+
+    assert.equal(poolBefore - (await lamportsOf(giveaway.pool)), amount);
+    assert.equal(
+      (await tokenBalanceOf(usdtAccount)) - tokenBefore,
+      tokenAmount
+    );
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.claimedCount, 1);
+    assert.equal(pool.bundle[0].remainingAmount.toNumber(), tokenAmount * 4);
+  });
+
+  it("refundBundleGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createBundleGiveaway(giveaway, 5, 100000000, 1000000);
+    await cancelGiveaway(giveaway);
+
+    const poolBefore = await lamportsOf(giveaway.pool);
+    const tokenBefore = await tokenBalanceOf(usdtAccount);
+    await refundBundle(giveaway);
+
+    assert.equal(poolBefore - (await lamportsOf(giveaway.pool)), 500000000);
+    assert.equal((await tokenBalanceOf(usdtAccount)) - tokenBefore, 5000000);
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.remainingAmount.toNumber(), 0);
+    assert.equal(pool.bundle[0].remainingAmount.toNumber(), 0);
+  });
+
+  it("closeBundleGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createBundleGiveaway(giveaway, 5, 100000000, 1000000);
+
+    const closeBundle = async () =>
+      program.methods
+        .closeBundle({ giveawayId: giveaway.giveawayId })
         .accounts({
           systemProgram: SYSTEM_PROGRAM_ID,
           tokenProgram: TOKEN_PROGRAM_ID,
-          rent: SYSVAR_RENT_PUBKEY,
-          payer: provider.wallet.publicKey,
-          giveawayPool: giveaway_pool,
-          fromAccount: usdt_ass,
-          tokenMint: new PublicKey(USDT),
-          tokenPool: token_pool,
+          payer,
+          giveawayPool: giveaway.pool,
         })
+        .remainingAccounts(await bundleRefundAccounts(giveaway))
         .rpc();
-      console.log("Your transaction signature", tx);
-    } catch (error) {
-      console.error(error);
-    }
+
+    await expectError(closeBundle(), "NotEmpty");
+
+    await cancelGiveaway(giveaway);
+    await refundBundle(giveaway);
+    await closeBundle();
+
+    const connection = provider.connection;
+    assert.isNull(await connection.getAccountInfo(giveaway.pool));
+    assert.isNull(await connection.getAccountInfo(await tokenPoolOf(giveaway)));
   });
 
-  it("receiveNonPutGiveaway", async () => {
-    const phrase =
-      "echo total link boy search leaf arch light rubber able include iron";
-    const newWallet = ethers.Wallet.fromPhrase(phrase);
-    const putGiveawayId = newWallet.address;
+  it("setGiveawayCount", async () => {
+    const giveaway = await newGiveaway();
+    // 位图红包：每个领取序号占 1 位，由签名方分配序号
+    await createPutGiveaway(giveaway, {
+      giveawayCount: 8,
+      recordMode: { bitmap: {} },
+      mode: { equal: {} },
+    }).rpc();
 
-    const [giveaway_pool] = await PublicKey.findProgramAddress(
-      [ethers.toBeArray(putGiveawayId)],
-      program.programId
-    );
+    await program.methods
+      .setGiveawayCount({
+        giveawayId: giveaway.giveawayId,
+        giveawayCount: 16,
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .rpc();
 
-    const [token_pool] = await PublicKey.findProgramAddress(
-      [provider.wallet.publicKey.toBytes(), new PublicKey(USDT).toBytes()],
-      program.programId
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.giveawayCount, 16);
+    assert.equal(pool.claimedBitmap.length, 2);
+
+    // 扩容后的序号可以领取
+    const claim = { wallet: payer, nonce: nextNonce(), index: 12 };
+    await receivePut(giveaway, claim, signClaim(giveaway, claim));
+    assert.equal((await fetchPool(giveaway)).claimedBitmap[1], 1 << 4);
+  });
+
+  it("topUpPutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    const balance = await lamportsOf(giveaway.pool);
+    await program.methods
+      .topUpPut({
+        giveawayId: giveaway.giveawayId,
+        amount: new anchor.BN(5000000000),
+      })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .rpc();
+
+    assert.equal((await lamportsOf(giveaway.pool)) - balance, 5000000000);
+    const pool = await fetchPool(giveaway);
+    assert.equal(pool.totalAmount.toNumber(), 15000000000);
+    assert.equal(pool.remainingAmount.toNumber(), 15000000000);
+  });
+
+  it("pauseGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    await program.methods
+      .pauseGiveaway({ giveawayId: giveaway.giveawayId })
+      .accounts({ payer, giveawayPool: giveaway.pool })
+      .rpc();
+    assert.deepEqual((await fetchPool(giveaway)).status, { paused: {} });
+
+    const claim = { wallet: payer, nonce: nextNonce(), amount: 1000000000 };
+    await expectError(
+      receivePut(giveaway, claim, signClaim(giveaway, claim)),
+      "Paused"
     );
+  });
+
+  it("resumeGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    await program.methods
+      .pauseGiveaway({ giveawayId: giveaway.giveawayId })
+      .accounts({ payer, giveawayPool: giveaway.pool })
+      .rpc();
+    await program.methods
+      .resumeGiveaway({ giveawayId: giveaway.giveawayId })
+      .accounts({ payer, giveawayPool: giveaway.pool })
+      .rpc();
+    assert.deepEqual((await fetchPool(giveaway)).status, { active: {} });
+
+    const claim = { wallet: payer, nonce: nextNonce(), amount: 1000000000 };
+    await receivePut(giveaway, claim, signClaim(giveaway, claim));
+    assert.equal((await fetchPool(giveaway)).claimedCount, 1);
+  });
+
+  it("cancelGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
 
-    // 找到一个USDT的关联账户
-    const usdt_ass = await getAssociatedTokenAddress(
-      new PublicKey(USDT),
-      provider.wallet.publicKey
+    await cancelGiveaway(giveaway);
+    assert.deepEqual((await fetchPool(giveaway)).status, { cancelled: {} });
+
+    const claim = { wallet: payer, nonce: nextNonce(), amount: 1000000000 };
+    await expectError(
+      receivePut(giveaway, claim, signClaim(giveaway, claim)),
+      "Cancelled"
     );
+  });
 
-    try {
-      const amount = 300000;
-      const timestamp = parseInt(
-        (Number(new Date().setFullYear(2025)) / 1000).toFixed(0)
-      );
-      // const timestamp = 1710755934;
-      const origin_message = ethers.solidityPacked(
-        ["bytes32", "address", "uint64", "uint128"],
-        [provider.wallet.publicKey.toBytes(), putGiveawayId, timestamp, amount]
-      );
+  it("setSigners", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
 
-      // const origin_message = putGiveawayId + receiveAddress.toString() + amount + timestamp;
+    // 签名服务的新密钥，与红包ID无关
+    const serviceWallet = ethers.Wallet.createRandom();
+    await program.methods
+      .setSigners({
+        giveawayId: giveaway.giveawayId,
+        signers: [
+          {
+            secp256k1: {
+              address: Array.from(ethers.getBytes(serviceWallet.address)),
+            },
+          },
+        ],
+      })
+      .accounts({ payer, giveawayPool: giveaway.pool })
+      .rpc();
 
-      const signature = await newWallet.signingKey.sign(
-        ethers.toBeArray(keccak256(origin_message))
-      ).serialized;
+    // 原签名方的签名失效，新签名方的签名可以领取
+    const claim = { wallet: payer, nonce: nextNonce(), amount: 1000000000 };
+    await expectError(
+      receivePut(giveaway, claim, signClaim(giveaway, claim)),
+      "Forbidden"
+    );
+    await receivePut(
+      giveaway,
+      claim,
+      signClaim(giveaway, claim, serviceWallet)
+    );
+    assert.equal((await fetchPool(giveaway)).claimedCount, 1);
+  });
 
-      const tx = await program.methods
-        .receiveNonPutGiveaway({
-          giveawayId: Array.from(ethers.toBeArray(putGiveawayId)),
-          amount: new anchor.BN(amount),
-          timestamp: new anchor.BN(timestamp),
-          signature: Array.from<number>(ethers.toBeArray(signature)),
-        })
+  it("closePutGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createPutGiveaway(giveaway).rpc();
+
+    const closePut = () =>
+      program.methods
+        .closePut({ giveawayId: giveaway.giveawayId })
         .accounts({
-          payer: provider.wallet.publicKey,
-          giveawayPool: giveaway_pool,
+          payer,
+          giveawayPool: giveaway.pool,
           systemProgram: SYSTEM_PROGRAM_ID,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          tokenMint: new PublicKey(USDT),
-          tokenPool: token_pool,
-          toAccount: usdt_ass,
         })
         .rpc();
-      console.log("Your transaction signature", tx);
-    } catch (error) {
-      console.error(error);
-    }
+
+    await expectError(closePut(), "NotEmpty");
+
+    await cancelGiveaway(giveaway);
+    await refundPut(giveaway);
+    await closePut();
+    assert.isNull(await provider.connection.getAccountInfo(giveaway.pool));
+  });
+
+  it("closeTokenGiveaway", async () => {
+    const giveaway = await newGiveaway();
+    await createNonPutGiveaway(giveaway);
+    await cancelGiveaway(giveaway);
+    await refundToken(giveaway);
+
+    const tokenPool = await tokenPoolOf(giveaway);
+    await program.methods
+      .closeToken({ giveawayId: giveaway.giveawayId })
+      .accounts({
+        payer,
+        giveawayPool: giveaway.pool,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMint: usdtMint,
+        tokenPool,
+        toAccount: usdtAccount,
+      })
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(giveaway.pool));
+    assert.isNull(await provider.connection.getAccountInfo(tokenPool));
   });
 });